@@ -0,0 +1,566 @@
+use super::{RemoteEntry, RemoteFeed, Service};
+use crate::{
+    config,
+    util::{crypto::md5_hex, time},
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::{cmp::Reverse, collections::HashMap, time::Duration};
+use tokio::sync::Mutex;
+
+const TIMEOUT: u64 = 30;
+
+// The server returns at most 50 items per request
+const MAX_PAGES: usize = 20;
+const MAX_WITH_IDS: usize = 50;
+
+// The items are pulled once for all the feeds in a sync round
+const REFRESH_INTERVAL: i64 = 60;
+
+// The oldest unsaved items are dropped, such as the items of the feeds which are never synced
+const MAX_CACHE_ITEMS: usize = 2000;
+
+// Some servers return the ids and flags as strings
+fn de_string<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    Ok(match Value::deserialize(d)? {
+        Value::String(v) => v,
+        Value::Number(v) => v.to_string(),
+        _ => String::default(),
+    })
+}
+
+fn de_i64<'de, D: Deserializer<'de>>(d: D) -> Result<i64, D::Error> {
+    Ok(match Value::deserialize(d)? {
+        Value::String(v) => v.parse().unwrap_or_default(),
+        Value::Number(v) => v.as_i64().unwrap_or_default(),
+        Value::Bool(v) => v as i64,
+        _ => 0,
+    })
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Group {
+    #[serde(deserialize_with = "de_string")]
+    id: String,
+
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct FeedsGroup {
+    #[serde(deserialize_with = "de_string")]
+    group_id: String,
+
+    #[serde(default, deserialize_with = "de_string")]
+    feed_ids: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Feed {
+    #[serde(deserialize_with = "de_string")]
+    id: String,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    url: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Item {
+    #[serde(deserialize_with = "de_string")]
+    id: String,
+
+    #[serde(deserialize_with = "de_string")]
+    feed_id: String,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    author: String,
+
+    #[serde(default)]
+    html: String,
+
+    #[serde(default)]
+    url: String,
+
+    #[serde(default, deserialize_with = "de_i64")]
+    is_saved: i64,
+
+    #[serde(default, deserialize_with = "de_i64")]
+    is_read: i64,
+
+    #[serde(default, deserialize_with = "de_i64")]
+    created_on_time: i64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Response {
+    #[serde(default, deserialize_with = "de_i64")]
+    auth: i64,
+
+    #[serde(default)]
+    groups: Vec<Group>,
+
+    #[serde(default)]
+    feeds: Vec<Feed>,
+
+    #[serde(default)]
+    feeds_groups: Vec<FeedsGroup>,
+
+    #[serde(default)]
+    items: Vec<Item>,
+
+    #[serde(default, deserialize_with = "de_string")]
+    unread_item_ids: String,
+
+    #[serde(default, deserialize_with = "de_string")]
+    saved_item_ids: String,
+}
+
+impl From<Item> for RemoteEntry {
+    fn from(item: Item) -> Self {
+        RemoteEntry {
+            id: item.id,
+            feed_id: item.feed_id,
+            url: item.url,
+            title: item.title,
+            author: item.author,
+            pub_date: time::rfc2822_from_timestamp(item.created_on_time),
+            tags: String::default(),
+            summary: item.html,
+            is_read: item.is_read != 0,
+            is_starred: item.is_saved != 0,
+        }
+    }
+}
+
+fn parse(text: &str) -> Result<Response> {
+    let res = serde_json::from_str::<Response>(text)?;
+    if res.auth != 1 {
+        bail!("authentication failed");
+    }
+    Ok(res)
+}
+
+fn split_ids(ids: &str) -> Vec<String> {
+    ids.split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+pub fn parse_feeds(groups_text: &str, feeds_text: &str) -> Result<Vec<RemoteFeed>> {
+    let groups = parse(groups_text)?;
+    let feeds = parse(feeds_text)?;
+
+    let mut categorys = HashMap::new();
+    for item in groups.feeds_groups.iter().chain(feeds.feeds_groups.iter()) {
+        let Some(group) = groups.groups.iter().find(|g| g.id == item.group_id) else {
+            continue;
+        };
+
+        for feed_id in split_ids(&item.feed_ids) {
+            categorys.entry(feed_id).or_insert(group.title.clone());
+        }
+    }
+
+    Ok(feeds
+        .feeds
+        .into_iter()
+        .map(|feed| RemoteFeed {
+            category: categorys.get(&feed.id).cloned().unwrap_or_default(),
+            id: feed.id,
+            title: feed.title,
+            url: feed.url,
+        })
+        .collect())
+}
+
+pub fn parse_items(text: &str) -> Result<Vec<RemoteEntry>> {
+    Ok(parse(text)?
+        .items
+        .into_iter()
+        .map(|item| item.into())
+        .collect())
+}
+
+fn item_id(item: &RemoteEntry) -> i64 {
+    item.id.parse().unwrap_or_default()
+}
+
+// The ids are increasing, so the latest ids are the largest ones
+fn latest_ids(ids: Vec<String>, counts: usize) -> Vec<String> {
+    let mut ids = ids
+        .into_iter()
+        .filter_map(|id| id.parse::<i64>().ok())
+        .collect::<Vec<_>>();
+    ids.sort_unstable_by_key(|id| Reverse(*id));
+    ids.into_iter()
+        .take(counts)
+        .map(|id| id.to_string())
+        .collect()
+}
+
+#[derive(Default)]
+struct Cache {
+    // the pulled items which haven't been saved locally
+    items: Vec<RemoteEntry>,
+
+    // the id of the latest pulled item
+    since_id: i64,
+    refresh_time: i64,
+}
+
+impl Cache {
+    fn push(&mut self, items: Vec<RemoteEntry>) {
+        for item in items.into_iter() {
+            self.since_id = i64::max(self.since_id, item_id(&item));
+            if !self.items.iter().any(|v| v.id == item.id) {
+                self.items.push(item);
+            }
+        }
+
+        if self.items.len() > MAX_CACHE_ITEMS {
+            self.items.sort_by_key(item_id);
+            let counts = self.items.len() - MAX_CACHE_ITEMS;
+            self.items.drain(..counts);
+        }
+    }
+
+    // Drop the items of the feed, and return the id up to which all the pulled items have been saved
+    fn saved(&mut self, feed_id: &str) -> i64 {
+        self.items.retain(|item| item.feed_id != feed_id);
+
+        match self.items.iter().map(item_id).min() {
+            Some(id) => id - 1,
+            _ => self.since_id,
+        }
+    }
+}
+
+// The Fever api of Tiny Tiny RSS, Miniflux and FreshRSS.
+// The url is the api endpoint without the `?api`, such as `https://miniflux.example.com/fever/`.
+pub struct Fever {
+    conf: config::Account,
+    api_key: String,
+    cache: Mutex<Cache>,
+}
+
+impl Fever {
+    pub fn new(conf: &config::Account) -> Self {
+        Self {
+            conf: conf.clone(),
+            api_key: md5_hex(&format!("{}:{}", conf.username, conf.password)).to_lowercase(),
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    async fn request(&self, query: &str, form: &[(&str, &str)]) -> Result<String> {
+        let base = self.conf.url.trim();
+        let base = base.strip_suffix("?api").unwrap_or(base);
+        let url = if query.is_empty() {
+            format!("{base}?api")
+        } else {
+            format!("{base}?api&{query}")
+        };
+
+        let mut form = form.to_vec();
+        form.push(("api_key", self.api_key.as_str()));
+
+        let res = super::client(&self.conf)?
+            .post(url)
+            .form(&form)
+            .timeout(Duration::from_secs(TIMEOUT))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        Ok(res.text().await?)
+    }
+
+    async fn ids(&self, name: &str) -> Result<Vec<String>> {
+        let res = parse(&self.request(name, &[]).await?)?;
+        Ok(split_ids(match name {
+            "unread_item_ids" => &res.unread_item_ids,
+            _ => &res.saved_item_ids,
+        }))
+    }
+
+    // Pull the new items since the last pulled item, and update the states of the pulled items.
+    // The saved `since_id` only moves forward after the items are saved locally.
+    async fn refresh(&self, cache: &mut Cache) -> Result<()> {
+        let mut since_id = i64::max(cache.since_id, config::account().since_id);
+        if since_id == 0 {
+            since_id = self.seed(cache).await?;
+        }
+
+        for _ in 0..MAX_PAGES {
+            let items = parse_items(
+                &self
+                    .request(&format!("items&since_id={since_id}"), &[])
+                    .await?,
+            )?;
+
+            if items.is_empty() {
+                break;
+            }
+
+            since_id = items.iter().map(item_id).fold(since_id, i64::max);
+            cache.push(items);
+        }
+        cache.since_id = since_id;
+
+        let unread_ids = self.ids("unread_item_ids").await?;
+        let saved_ids = self.ids("saved_item_ids").await?;
+        for item in cache.items.iter_mut() {
+            item.is_read = !unread_ids.contains(&item.id);
+            item.is_starred = saved_ids.contains(&item.id);
+        }
+
+        cache.refresh_time = Utc::now().timestamp();
+        Ok(())
+    }
+
+    // Paging from the oldest item takes too many requests on the first sync,
+    // so the latest unread items are pulled by their ids instead
+    async fn seed(&self, cache: &mut Cache) -> Result<i64> {
+        let unread_ids = latest_ids(self.ids("unread_item_ids").await?, MAX_CACHE_ITEMS);
+
+        for ids in unread_ids.chunks(MAX_WITH_IDS) {
+            let items = parse_items(
+                &self
+                    .request(&format!("items&with_ids={}", ids.join(",")), &[])
+                    .await?,
+            )?;
+            cache.push(items);
+        }
+
+        Ok(cache.since_id)
+    }
+
+    async fn mark(&self, entry_ids: &[String], state: &str) -> Result<()> {
+        for id in entry_ids.iter() {
+            parse(
+                &self
+                    .request("", &[("mark", "item"), ("as", state), ("id", id)])
+                    .await?,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Service for Fever {
+    async fn login(&mut self) -> Result<()> {
+        parse(&self.request("", &[]).await?)?;
+        Ok(())
+    }
+
+    async fn feeds(&self) -> Result<Vec<RemoteFeed>> {
+        let groups_text = self.request("groups", &[]).await?;
+        let feeds_text = self.request("feeds", &[]).await?;
+        parse_feeds(&groups_text, &feeds_text)
+    }
+
+    async fn entries(&self, feed_id: &str) -> Result<Vec<RemoteEntry>> {
+        let mut cache = self.cache.lock().await;
+        if Utc::now().timestamp() - cache.refresh_time > REFRESH_INTERVAL {
+            self.refresh(&mut cache).await?;
+        }
+
+        Ok(cache
+            .items
+            .iter()
+            .filter(|item| item.feed_id == feed_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn saved(&self, feed_id: &str) -> Result<()> {
+        let since_id = self.cache.lock().await.saved(feed_id);
+
+        let mut all = config::all();
+        if since_id > all.account.since_id {
+            all.account.since_id = since_id;
+            config::save(all)?;
+        }
+        Ok(())
+    }
+
+    // The saved items which haven't been pulled are fetched by their ids
+    async fn starred_entries(&self) -> Result<Vec<RemoteEntry>> {
+        let saved_ids = self.ids("saved_item_ids").await?;
+        let cache = self.cache.lock().await;
+
+        let mut entries = cache
+            .items
+            .iter()
+            .filter(|item| saved_ids.contains(&item.id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let unpulled_ids = saved_ids
+            .into_iter()
+            .filter(|id| !entries.iter().any(|item| &item.id == id))
+            .collect::<Vec<_>>();
+
+        for ids in unpulled_ids.chunks(MAX_WITH_IDS) {
+            let items = parse_items(
+                &self
+                    .request(&format!("items&with_ids={}", ids.join(",")), &[])
+                    .await?,
+            )?;
+            entries.extend(items);
+        }
+
+        for item in entries.iter_mut() {
+            item.is_starred = true;
+        }
+
+        Ok(entries)
+    }
+
+    async fn set_read(&self, entry_ids: &[String], is_read: bool) -> Result<()> {
+        self.mark(entry_ids, if is_read { "read" } else { "unread" })
+            .await
+    }
+
+    async fn set_starred(&self, entry_ids: &[String], is_starred: bool) -> Result<()> {
+        self.mark(entry_ids, if is_starred { "saved" } else { "unsaved" })
+            .await
+    }
+
+    // The Fever api can't manage the subscriptions, so the new feeds are fetched directly
+    async fn subscribe(&self, _url: &str, _title: &str) -> Result<String> {
+        Ok(String::default())
+    }
+
+    async fn unsubscribe(&self, _feed_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feeds() -> Result<()> {
+        let groups_text = r#"{"api_version":3,"auth":1,
+            "groups":[{"id":1,"title":"Tech"},{"id":"2","title":"News"}],
+            "feeds_groups":[{"group_id":1,"feed_ids":"1,3"},{"group_id":2,"feed_ids":"2"}]}"#;
+        let feeds_text = r#"{"api_version":3,"auth":1,
+            "feeds":[
+                {"id":1,"favicon_id":1,"title":"Rust Blog","url":"https://blog.rust-lang.org/feed.xml","site_url":"https://blog.rust-lang.org","is_spark":0,"last_updated_on_time":1700000000},
+                {"id":2,"title":"News","url":"https://example.com/rss"},
+                {"id":4,"title":"Other","url":"https://example.com/other"}
+            ]}"#;
+
+        let feeds = parse_feeds(groups_text, feeds_text)?;
+        assert_eq!(feeds.len(), 3);
+        assert_eq!(
+            feeds[0],
+            RemoteFeed {
+                id: "1".to_string(),
+                title: "Rust Blog".to_string(),
+                url: "https://blog.rust-lang.org/feed.xml".to_string(),
+                category: "Tech".to_string(),
+            }
+        );
+        assert_eq!(feeds[1].category, "News");
+        assert!(feeds[2].category.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_items() -> Result<()> {
+        let text = r#"{"api_version":3,"auth":1,"total_items":2,"items":[
+            {"id":10,"feed_id":1,"title":"Hello","author":"alice","html":"<p>world</p>",
+             "url":"https://example.com/hello","is_saved":1,"is_read":0,"created_on_time":1700000000},
+            {"id":"11","feed_id":"2","title":"Read","html":"","url":"https://example.com/read",
+             "is_saved":"0","is_read":"1","created_on_time":"1700000100"}
+        ]}"#;
+
+        let items = parse_items(text)?;
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].id, "10");
+        assert_eq!(items[0].feed_id, "1");
+        assert_eq!(items[0].summary, "<p>world</p>");
+        assert!(!items[0].is_read && items[0].is_starred);
+        assert!(!items[0].pub_date.is_empty());
+
+        assert_eq!(items[1].id, "11");
+        assert!(items[1].is_read && !items[1].is_starred);
+
+        assert!(parse_items(r#"{"api_version":3,"auth":0}"#).is_err());
+        Ok(())
+    }
+
+    fn remote_entry(id: i64, feed_id: &str) -> RemoteEntry {
+        RemoteEntry {
+            id: id.to_string(),
+            feed_id: feed_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_saved() {
+        let mut cache = Cache::default();
+        cache.push(vec![
+            remote_entry(10, "1"),
+            remote_entry(11, "2"),
+            remote_entry(12, "1"),
+        ]);
+        cache.push(vec![remote_entry(12, "1")]);
+        assert_eq!(cache.items.len(), 3);
+        assert_eq!(cache.since_id, 12);
+
+        // the item 11 of the feed 2 hasn't been saved
+        assert_eq!(cache.saved("1"), 10);
+        assert_eq!(cache.items.len(), 1);
+
+        assert_eq!(cache.saved("2"), 12);
+        assert!(cache.items.is_empty());
+    }
+
+    #[test]
+    fn test_latest_ids() {
+        let ids = ["3", "12", "x", "7"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(latest_ids(ids.clone(), 2), vec!["12", "7"]);
+        assert_eq!(latest_ids(ids, 10), vec!["12", "7", "3"]);
+        assert!(latest_ids(vec![], 10).is_empty());
+    }
+
+    #[test]
+    fn test_cache_limit() {
+        let mut cache = Cache::default();
+        cache.push(
+            (0..MAX_CACHE_ITEMS as i64 + 10)
+                .rev()
+                .map(|id| remote_entry(id, "1"))
+                .collect(),
+        );
+
+        assert_eq!(cache.items.len(), MAX_CACHE_ITEMS);
+        assert_eq!(cache.items[0].id, "10");
+        assert_eq!(cache.saved("2"), 9);
+    }
+}
@@ -0,0 +1,415 @@
+use super::{RemoteEntry, RemoteFeed, Service};
+use crate::{config, util::time};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::{header::AUTHORIZATION, RequestBuilder, Url};
+use serde::Deserialize;
+use std::time::Duration;
+
+const TIMEOUT: u64 = 30;
+const MAX_ENTRIES: u32 = 100;
+
+const STATE_READ: &str = "user/-/state/com.google/read";
+const STATE_STARRED: &str = "user/-/state/com.google/starred";
+
+#[derive(Deserialize, Debug, Default)]
+struct Category {
+    #[serde(default)]
+    label: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Subscription {
+    id: String,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    url: String,
+
+    #[serde(default)]
+    categories: Vec<Category>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct SubscriptionList {
+    #[serde(default)]
+    subscriptions: Vec<Subscription>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Link {
+    #[serde(default)]
+    href: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Content {
+    #[serde(default)]
+    content: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct Origin {
+    #[serde(default)]
+    stream_id: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Item {
+    id: String,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    author: String,
+
+    #[serde(default)]
+    published: i64,
+
+    #[serde(default)]
+    canonical: Vec<Link>,
+
+    #[serde(default)]
+    alternate: Vec<Link>,
+
+    #[serde(default)]
+    summary: Option<Content>,
+
+    #[serde(default)]
+    content: Option<Content>,
+
+    #[serde(default)]
+    categories: Vec<String>,
+
+    #[serde(default)]
+    origin: Origin,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Stream {
+    #[serde(default)]
+    items: Vec<Item>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct QuickAdd {
+    #[serde(default)]
+    stream_id: String,
+}
+
+// The servers may return the user id instead of `-`, such as `user/1000/state/com.google/read`
+fn has_state(categories: &[String], state: &str) -> bool {
+    let suffix = state.trim_start_matches("user/-");
+    categories
+        .iter()
+        .any(|c| c.starts_with("user/") && c.ends_with(suffix))
+}
+
+impl From<Item> for RemoteEntry {
+    fn from(item: Item) -> Self {
+        let url = item
+            .canonical
+            .into_iter()
+            .chain(item.alternate)
+            .map(|link| link.href)
+            .find(|href| !href.is_empty())
+            .unwrap_or_default();
+
+        let summary = item
+            .summary
+            .or(item.content)
+            .map(|c| c.content)
+            .unwrap_or_default();
+
+        let tags = item
+            .categories
+            .iter()
+            .filter_map(|c| c.strip_prefix("user/")?.split_once("/label/"))
+            .map(|(_, label)| label)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        RemoteEntry {
+            feed_id: item.origin.stream_id,
+            url,
+            title: item.title,
+            author: item.author,
+            pub_date: time::rfc2822_from_timestamp(item.published),
+            tags,
+            summary,
+            is_read: has_state(&item.categories, STATE_READ),
+            is_starred: has_state(&item.categories, STATE_STARRED),
+            id: item.id,
+        }
+    }
+}
+
+// The `Auth` line in the response of `ClientLogin`
+pub fn parse_login(text: &str) -> Result<String> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("Auth="))
+        .map(|v| v.to_string())
+        .context("no auth token in the response")
+}
+
+pub fn parse_subscriptions(text: &str) -> Result<Vec<RemoteFeed>> {
+    let list = serde_json::from_str::<SubscriptionList>(text)?;
+
+    Ok(list
+        .subscriptions
+        .into_iter()
+        .map(|item| RemoteFeed {
+            category: item
+                .categories
+                .into_iter()
+                .next()
+                .map(|c| c.label)
+                .unwrap_or_default(),
+            id: item.id,
+            title: item.title,
+            url: item.url,
+        })
+        .collect())
+}
+
+pub fn parse_stream(text: &str) -> Result<Vec<RemoteEntry>> {
+    let stream = serde_json::from_str::<Stream>(text)?;
+    Ok(stream.items.into_iter().map(|item| item.into()).collect())
+}
+
+// The Google Reader api which is implemented by FreshRSS, Inoreader and The Old Reader.
+// The url is the api root, such as `https://freshrss.example.com/api/greader.php`.
+pub struct GReader {
+    conf: config::Account,
+    auth: String,
+}
+
+impl GReader {
+    pub fn new(conf: &config::Account) -> Self {
+        Self {
+            conf: conf.clone(),
+            auth: String::default(),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: Url) -> Result<RequestBuilder> {
+        Ok(super::client(&self.conf)?
+            .request(method, url)
+            .header(AUTHORIZATION, format!("GoogleLogin auth={}", self.auth))
+            .timeout(Duration::from_secs(TIMEOUT)))
+    }
+
+    async fn get(&self, url: Url) -> Result<String> {
+        let res = self.request(reqwest::Method::GET, url)?.send().await?;
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        Ok(res.text().await?)
+    }
+
+    // The write requests need a short-lived token
+    async fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<String> {
+        let token = self
+            .get(super::join_url(&self.conf.url, "reader/api/0/token")?)
+            .await?;
+
+        let mut form = form.to_vec();
+        form.push(("T", token.trim()));
+
+        let res = self
+            .request(
+                reqwest::Method::POST,
+                super::join_url(&self.conf.url, path)?,
+            )?
+            .form(&form)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        Ok(res.text().await?)
+    }
+
+    async fn stream(&self, stream_id: &str) -> Result<Vec<RemoteEntry>> {
+        let mut url = super::join_url(&self.conf.url, "reader/api/0/stream/contents")?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("invalid url"))?
+            .pop_if_empty()
+            .push(stream_id);
+        url.query_pairs_mut()
+            .append_pair("output", "json")
+            .append_pair("n", &MAX_ENTRIES.to_string());
+
+        parse_stream(&self.get(url).await?)
+    }
+
+    async fn edit_tag(&self, entry_ids: &[String], tag: &str, is_add: bool) -> Result<()> {
+        if entry_ids.is_empty() {
+            return Ok(());
+        }
+
+        let mut form = entry_ids
+            .iter()
+            .map(|id| ("i", id.as_str()))
+            .collect::<Vec<_>>();
+        form.push((if is_add { "a" } else { "r" }, tag));
+
+        self.post("reader/api/0/edit-tag", &form).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Service for GReader {
+    async fn login(&mut self) -> Result<()> {
+        let res = super::client(&self.conf)?
+            .post(super::join_url(&self.conf.url, "accounts/ClientLogin")?)
+            .form(&[
+                ("Email", self.conf.username.as_str()),
+                ("Passwd", self.conf.password.as_str()),
+            ])
+            .timeout(Duration::from_secs(TIMEOUT))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            bail!("login failed. http error code: {}", res.status().as_str());
+        }
+
+        self.auth = parse_login(&res.text().await?)?;
+        Ok(())
+    }
+
+    async fn feeds(&self) -> Result<Vec<RemoteFeed>> {
+        let mut url = super::join_url(&self.conf.url, "reader/api/0/subscription/list")?;
+        url.query_pairs_mut().append_pair("output", "json");
+        parse_subscriptions(&self.get(url).await?)
+    }
+
+    async fn entries(&self, feed_id: &str) -> Result<Vec<RemoteEntry>> {
+        self.stream(feed_id).await
+    }
+
+    async fn starred_entries(&self) -> Result<Vec<RemoteEntry>> {
+        self.stream(STATE_STARRED).await
+    }
+
+    async fn set_read(&self, entry_ids: &[String], is_read: bool) -> Result<()> {
+        self.edit_tag(entry_ids, STATE_READ, is_read).await
+    }
+
+    async fn set_starred(&self, entry_ids: &[String], is_starred: bool) -> Result<()> {
+        self.edit_tag(entry_ids, STATE_STARRED, is_starred).await
+    }
+
+    async fn subscribe(&self, url: &str, title: &str) -> Result<String> {
+        let text = self
+            .post("reader/api/0/subscription/quickadd", &[("quickadd", url)])
+            .await?;
+        let feed_id = serde_json::from_str::<QuickAdd>(&text)?.stream_id;
+
+        if feed_id.is_empty() {
+            bail!("subscribe {url} failed");
+        }
+
+        if !title.is_empty() {
+            self.post(
+                "reader/api/0/subscription/edit",
+                &[("ac", "edit"), ("s", feed_id.as_str()), ("t", title)],
+            )
+            .await?;
+        }
+
+        Ok(feed_id)
+    }
+
+    async fn unsubscribe(&self, feed_id: &str) -> Result<()> {
+        self.post(
+            "reader/api/0/subscription/edit",
+            &[("ac", "unsubscribe"), ("s", feed_id)],
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_login() -> Result<()> {
+        let text = "SID=none\nLSID=none\nAuth=alice/8e6845e089457af25303abc6f53356eb60bdb5f8\n";
+        assert_eq!(
+            parse_login(text)?,
+            "alice/8e6845e089457af25303abc6f53356eb60bdb5f8"
+        );
+        assert!(parse_login("Error=BadAuthentication").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_subscriptions() -> Result<()> {
+        let text = r#"{"subscriptions":[
+            {"id":"feed/1","title":"Rust Blog","url":"https://blog.rust-lang.org/feed.xml",
+             "htmlUrl":"https://blog.rust-lang.org","categories":[{"id":"user/-/label/Tech","label":"Tech"}]},
+            {"id":"feed/2","title":"News","url":"https://example.com/rss"}
+        ]}"#;
+
+        let feeds = parse_subscriptions(text)?;
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(
+            feeds[0],
+            RemoteFeed {
+                id: "feed/1".to_string(),
+                title: "Rust Blog".to_string(),
+                url: "https://blog.rust-lang.org/feed.xml".to_string(),
+                category: "Tech".to_string(),
+            }
+        );
+        assert!(feeds[1].category.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stream() -> Result<()> {
+        let text = r#"{"id":"feed/1","items":[
+            {"id":"tag:google.com,2005:reader/item/0000000000000001","title":"Hello",
+             "published":1700000000,"author":"alice",
+             "alternate":[{"href":"https://example.com/hello","type":"text/html"}],
+             "summary":{"content":"<p>world</p>"},
+             "categories":["user/-/state/com.google/reading-list","user/-/state/com.google/read","user/-/label/Tech"],
+             "origin":{"streamId":"feed/1","title":"News"}},
+            {"id":"tag:google.com,2005:reader/item/0000000000000002","title":"Starred",
+             "canonical":[{"href":"https://example.com/starred"}],
+             "content":{"content":"text"},
+             "categories":["user/1000/state/com.google/starred","user/1000/label/Rust"],
+             "origin":{"streamId":"feed/1"}}
+        ]}"#;
+
+        let entries = parse_stream(text)?;
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].url, "https://example.com/hello");
+        assert_eq!(entries[0].summary, "<p>world</p>");
+        assert_eq!(entries[0].tags, "Tech");
+        assert_eq!(entries[0].feed_id, "feed/1");
+        assert!(entries[0].is_read && !entries[0].is_starred);
+        assert!(!entries[0].pub_date.is_empty());
+
+        assert_eq!(entries[1].url, "https://example.com/starred");
+        assert_eq!(entries[1].summary, "text");
+        assert_eq!(entries[1].tags, "Rust");
+        assert!(!entries[1].is_read && entries[1].is_starred);
+        Ok(())
+    }
+}
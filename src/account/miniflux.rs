@@ -0,0 +1,332 @@
+use super::{RemoteEntry, RemoteFeed, Service};
+use crate::config;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::DateTime;
+use reqwest::{Method, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const TIMEOUT: u64 = 30;
+const MAX_ENTRIES: u32 = 100;
+
+#[derive(Deserialize, Debug, Default)]
+struct Category {
+    #[serde(default)]
+    id: i64,
+
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Feed {
+    id: i64,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    feed_url: String,
+
+    #[serde(default)]
+    category: Option<Category>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Entry {
+    id: i64,
+
+    #[serde(default)]
+    feed_id: i64,
+
+    #[serde(default)]
+    title: String,
+
+    #[serde(default)]
+    url: String,
+
+    #[serde(default)]
+    author: String,
+
+    #[serde(default)]
+    content: String,
+
+    #[serde(default)]
+    published_at: String,
+
+    #[serde(default)]
+    status: String,
+
+    #[serde(default)]
+    starred: bool,
+
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Entries {
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Debug)]
+struct UpdateEntries {
+    entry_ids: Vec<i64>,
+    status: String,
+}
+
+#[derive(Serialize, Debug)]
+struct CreateFeed {
+    feed_url: String,
+    category_id: i64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct CreatedFeed {
+    feed_id: i64,
+}
+
+impl From<Entry> for RemoteEntry {
+    fn from(entry: Entry) -> Self {
+        RemoteEntry {
+            id: entry.id.to_string(),
+            feed_id: entry.feed_id.to_string(),
+            url: entry.url,
+            title: entry.title,
+            author: entry.author,
+            pub_date: DateTime::parse_from_rfc3339(&entry.published_at)
+                .map(|t| t.to_rfc2822())
+                .unwrap_or_default(),
+            tags: entry.tags.unwrap_or_default().join(","),
+            summary: entry.content,
+            is_read: entry.status == "read",
+            is_starred: entry.starred,
+        }
+    }
+}
+
+pub fn parse_feeds(text: &str) -> Result<Vec<RemoteFeed>> {
+    Ok(serde_json::from_str::<Vec<Feed>>(text)?
+        .into_iter()
+        .map(|feed| RemoteFeed {
+            id: feed.id.to_string(),
+            title: feed.title,
+            url: feed.feed_url,
+            category: feed.category.map(|c| c.title).unwrap_or_default(),
+        })
+        .collect())
+}
+
+pub fn parse_entries(text: &str) -> Result<Vec<RemoteEntry>> {
+    Ok(serde_json::from_str::<Entries>(text)?
+        .entries
+        .into_iter()
+        .map(|entry| entry.into())
+        .collect())
+}
+
+fn parse_ids(ids: &[String]) -> Vec<i64> {
+    ids.iter().filter_map(|id| id.parse().ok()).collect()
+}
+
+// The Miniflux REST api. The url is the root of the server, such as `https://miniflux.example.com`.
+// The password is used as the api token if the username is empty.
+pub struct Miniflux {
+    conf: config::Account,
+}
+
+impl Miniflux {
+    pub fn new(conf: &config::Account) -> Self {
+        Self { conf: conf.clone() }
+    }
+
+    fn request(&self, method: Method, path: &str) -> Result<RequestBuilder> {
+        let req = super::client(&self.conf)?
+            .request(method, super::join_url(&self.conf.url, path)?)
+            .timeout(Duration::from_secs(TIMEOUT));
+
+        Ok(if self.conf.username.is_empty() {
+            req.header("X-Auth-Token", &self.conf.password)
+        } else {
+            req.basic_auth(&self.conf.username, Some(&self.conf.password))
+        })
+    }
+
+    async fn send(&self, req: RequestBuilder) -> Result<Response> {
+        let res = req.send().await?;
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+        Ok(res)
+    }
+
+    async fn get(&self, path: &str) -> Result<String> {
+        Ok(self
+            .send(self.request(Method::GET, path)?)
+            .await?
+            .text()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl Service for Miniflux {
+    async fn login(&mut self) -> Result<()> {
+        self.get("v1/me").await?;
+        Ok(())
+    }
+
+    async fn refresh(&self) -> Result<()> {
+        self.send(self.request(Method::PUT, "v1/feeds/refresh")?)
+            .await?;
+        Ok(())
+    }
+
+    async fn feeds(&self) -> Result<Vec<RemoteFeed>> {
+        parse_feeds(&self.get("v1/feeds").await?)
+    }
+
+    async fn entries(&self, feed_id: &str) -> Result<Vec<RemoteEntry>> {
+        parse_entries(
+            &self
+                .get(&format!(
+                    "v1/feeds/{feed_id}/entries?limit={MAX_ENTRIES}&order=published_at&direction=desc"
+                ))
+                .await?,
+        )
+    }
+
+    async fn starred_entries(&self) -> Result<Vec<RemoteEntry>> {
+        parse_entries(
+            &self
+                .get(&format!(
+                    "v1/entries?starred=true&limit={MAX_ENTRIES}&order=published_at&direction=desc"
+                ))
+                .await?,
+        )
+    }
+
+    async fn set_read(&self, entry_ids: &[String], is_read: bool) -> Result<()> {
+        let entry_ids = parse_ids(entry_ids);
+        if entry_ids.is_empty() {
+            return Ok(());
+        }
+
+        let body = UpdateEntries {
+            entry_ids,
+            status: if is_read { "read" } else { "unread" }.to_string(),
+        };
+
+        self.send(self.request(Method::PUT, "v1/entries")?.json(&body))
+            .await?;
+        Ok(())
+    }
+
+    // The bookmark api toggles the starred state, so only the entries in the other state are toggled
+    async fn set_starred(&self, entry_ids: &[String], is_starred: bool) -> Result<()> {
+        for id in parse_ids(entry_ids) {
+            let entry =
+                serde_json::from_str::<Entry>(&self.get(&format!("v1/entries/{id}")).await?)?;
+            if entry.starred == is_starred {
+                continue;
+            }
+
+            self.send(self.request(Method::PUT, &format!("v1/entries/{id}/bookmark"))?)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // The new feeds are added to the first category
+    async fn subscribe(&self, url: &str, _title: &str) -> Result<String> {
+        let categories = serde_json::from_str::<Vec<Category>>(&self.get("v1/categories").await?)?;
+        let category_id = categories
+            .first()
+            .map(|c| c.id)
+            .context("no category in the server")?;
+
+        let body = CreateFeed {
+            feed_url: url.to_string(),
+            category_id,
+        };
+
+        let text = self
+            .send(self.request(Method::POST, "v1/feeds")?.json(&body))
+            .await?
+            .text()
+            .await?;
+
+        Ok(serde_json::from_str::<CreatedFeed>(&text)?
+            .feed_id
+            .to_string())
+    }
+
+    async fn unsubscribe(&self, feed_id: &str) -> Result<()> {
+        self.send(self.request(Method::DELETE, &format!("v1/feeds/{feed_id}"))?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_feeds() -> Result<()> {
+        let text = r#"[
+            {"id":42,"user_id":1,"title":"Rust Blog","site_url":"https://blog.rust-lang.org",
+             "feed_url":"https://blog.rust-lang.org/feed.xml","category":{"id":22,"title":"Tech","user_id":1}},
+            {"id":43,"title":"News","feed_url":"https://example.com/rss","category":null}
+        ]"#;
+
+        let feeds = parse_feeds(text)?;
+        assert_eq!(feeds.len(), 2);
+        assert_eq!(
+            feeds[0],
+            RemoteFeed {
+                id: "42".to_string(),
+                title: "Rust Blog".to_string(),
+                url: "https://blog.rust-lang.org/feed.xml".to_string(),
+                category: "Tech".to_string(),
+            }
+        );
+        assert!(feeds[1].category.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_entries() -> Result<()> {
+        let text = r#"{"total":2,"entries":[
+            {"id":888,"user_id":1,"feed_id":42,"status":"unread","title":"Hello",
+             "url":"https://example.com/hello","author":"alice","content":"<p>world</p>",
+             "published_at":"2023-11-14T22:13:20Z","starred":true,"tags":["rust","release"]},
+            {"id":889,"feed_id":42,"status":"read","title":"Read","url":"https://example.com/read",
+             "content":"","published_at":"invalid","starred":false,"tags":null}
+        ]}"#;
+
+        let entries = parse_entries(text)?;
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].id, "888");
+        assert_eq!(entries[0].feed_id, "42");
+        assert_eq!(entries[0].tags, "rust,release");
+        assert_eq!(entries[0].pub_date, "Tue, 14 Nov 2023 22:13:20 +0000");
+        assert!(!entries[0].is_read && entries[0].is_starred);
+
+        assert!(entries[1].is_read && !entries[1].is_starred);
+        assert!(entries[1].pub_date.is_empty());
+        assert!(entries[1].tags.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ids() {
+        assert_eq!(
+            parse_ids(&["1".to_string(), "x".to_string(), "3".to_string()]),
+            vec![1, 3]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    config,
+    util::http::{self, ProxyType},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Client, Url};
+
+pub mod fever;
+pub mod greader;
+pub mod miniflux;
+
+pub use fever::Fever;
+pub use greader::GReader;
+pub use miniflux::Miniflux;
+
+// The kinds of the account. An empty kind means the feeds are fetched directly.
+pub const KIND_DIRECT: &str = "";
+pub const KIND_GREADER: &str = "greader";
+pub const KIND_FEVER: &str = "fever";
+pub const KIND_MINIFLUX: &str = "miniflux";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteFeed {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RemoteEntry {
+    pub id: String,
+    pub feed_id: String,
+    pub url: String,
+    pub title: String,
+    pub author: String,
+    pub pub_date: String,
+    pub tags: String,
+    pub summary: String,
+    pub is_read: bool,
+    pub is_starred: bool,
+}
+
+// A feed aggregation server which keeps the subscriptions, the entries and their states
+#[async_trait]
+pub trait Service: Send + Sync {
+    async fn login(&mut self) -> Result<()>;
+
+    // Ask the server to fetch the feeds, so that the phone doesn't need to fetch every feed
+    async fn refresh(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn feeds(&self) -> Result<Vec<RemoteFeed>>;
+
+    // the latest entries of the feed
+    async fn entries(&self, feed_id: &str) -> Result<Vec<RemoteEntry>>;
+
+    // the entries of the feed have been saved locally, so they needn't be returned again
+    async fn saved(&self, _feed_id: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn starred_entries(&self) -> Result<Vec<RemoteEntry>>;
+
+    async fn set_read(&self, entry_ids: &[String], is_read: bool) -> Result<()>;
+
+    async fn set_starred(&self, entry_ids: &[String], is_starred: bool) -> Result<()>;
+
+    // return the id of the new feed. An empty id means the server can't
+    // manage the subscriptions, and the feed is fetched directly.
+    async fn subscribe(&self, url: &str, title: &str) -> Result<String>;
+
+    async fn unsubscribe(&self, feed_id: &str) -> Result<()>;
+}
+
+pub fn is_enabled(conf: &config::Account) -> bool {
+    conf.kind != KIND_DIRECT && !conf.url.trim().is_empty()
+}
+
+pub fn proxy_type(conf: &config::Account) -> Option<ProxyType> {
+    if conf.use_http_proxy {
+        Some(ProxyType::Http)
+    } else if conf.use_socks5_proxy {
+        Some(ProxyType::Socks5)
+    } else {
+        None
+    }
+}
+
+pub fn client(conf: &config::Account) -> Result<Client> {
+    Ok(http::client(proxy_type(conf))?)
+}
+
+// Append the path to the base url, the trailing slash of the base url is ignored
+pub fn join_url(base: &str, path: &str) -> Result<Url> {
+    Ok(Url::parse(&format!(
+        "{}/{}",
+        base.trim().trim_end_matches('/'),
+        path.trim_start_matches('/')
+    ))?)
+}
+
+pub async fn connect(conf: &config::Account) -> Result<Box<dyn Service>> {
+    let mut service: Box<dyn Service> = match conf.kind.as_str() {
+        KIND_GREADER => Box::new(GReader::new(conf)),
+        KIND_FEVER => Box::new(Fever::new(conf)),
+        KIND_MINIFLUX => Box::new(Miniflux::new(conf)),
+        kind => anyhow::bail!("unknown account kind: {kind}"),
+    };
+
+    service.login().await?;
+    Ok(service)
+}
@@ -0,0 +1,309 @@
+extern crate rssbox;
+
+use anyhow::{bail, Context, Result};
+use axum::{
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use clap::Parser;
+use rssbox::{
+    db::ComEntry,
+    logic::FindEntry,
+    util::{crypto::md5_hex, url},
+};
+use serde_json::Value;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+use uuid::Uuid;
+
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+const LATEST_VERSION_FILE: &str = "latest-version.json";
+const FEEDBACK_FILE: &str = "feedback.jsonl";
+
+/// Reference api server for backup, recover, feedback and the find list.
+/// Set the app's server url to `http://<listen address>` to use it.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Listen address
+    #[arg(short, long, default_value_t = String::from("0.0.0.0:8004"))]
+    listen: String,
+
+    /// Directory to save the backups, the find lists and the feedbacks
+    #[arg(short, long, default_value_t = String::from("./apisvr-data"))]
+    data_dir: String,
+
+    /// Allowed api tokens, separated by comma
+    #[arg(short, long, default_value_t = String::default())]
+    tokens: String,
+
+    /// Allow any non-empty api token. Only use it in a trusted network
+    #[arg(long, default_value_t = false)]
+    open: bool,
+}
+
+struct AppState {
+    data_dir: PathBuf,
+    tokens: Vec<String>,
+    open: bool,
+
+    // serialize the file writings
+    lock: Mutex<()>,
+}
+
+type AppError = (StatusCode, String);
+
+fn internal_error(e: impl std::fmt::Display) -> AppError {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+#[cfg(not(target_os = "android"))]
+#[tokio::main]
+async fn main() -> Result<()> {
+    rssbox::init_logger();
+
+    let args = Args::parse();
+    let tokens = args
+        .tokens
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect::<Vec<_>>();
+
+    if tokens.is_empty() && !args.open {
+        bail!("no api tokens. Set the tokens with `--tokens`, or allow any token with `--open`");
+    }
+
+    let data_dir = PathBuf::from(&args.data_dir);
+    fs::create_dir_all(data_dir.join("backup"))
+        .await
+        .context("create data directory failed")?;
+
+    let state = Arc::new(AppState {
+        data_dir,
+        tokens,
+        open: args.open,
+        lock: Mutex::new(()),
+    });
+
+    let app = Router::new()
+        .route("/rssbox/android/backup", post(backup))
+        .route("/rssbox/android/recover", get(recover))
+        .route("/rssbox/android/feedback", post(feedback))
+        .route(
+            "/rssbox/rss/list/:language",
+            get(rss_list).post(add_rss).delete(remove_rss),
+        )
+        .route("/latest/version", get(latest_version))
+        .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
+        .with_state(state);
+
+    log::info!("listen on {}", args.listen);
+
+    let listener = tokio::net::TcpListener::bind(&args.listen)
+        .await
+        .with_context(|| format!("bind {} failed", args.listen))?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+// The old clients send the token in the query string, so accept both
+fn api_token(
+    state: &AppState,
+    headers: &HeaderMap,
+    query: &HashMap<String, String>,
+) -> Result<String, AppError> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string())
+        .or(query.get("api_token").map(|v| v.trim().to_string()))
+        .unwrap_or_default();
+
+    if token.is_empty() || (!state.open && !state.tokens.contains(&token)) {
+        return Err((StatusCode::UNAUTHORIZED, "invalid api token".to_string()));
+    }
+
+    Ok(token)
+}
+
+// Use the md5 of the token as the file name, so that the token can't escape the directory
+fn backup_path(state: &AppState, token: &str) -> PathBuf {
+    state
+        .data_dir
+        .join("backup")
+        .join(format!("{}.json", md5_hex(token)))
+}
+
+fn rss_list_path(state: &AppState, language: &str) -> Result<PathBuf, AppError> {
+    match language {
+        "cn" | "en" => Ok(state.data_dir.join(format!("rss-list-{language}.json"))),
+        _ => Err((StatusCode::NOT_FOUND, "unknown language".to_string())),
+    }
+}
+
+async fn backup(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    Json(data): Json<Value>,
+) -> Result<StatusCode, AppError> {
+    let token = api_token(&state, &headers, &query)?;
+    let text = serde_json::to_string(&data).map_err(internal_error)?;
+
+    let _lock = state.lock.lock().await;
+    fs::write(backup_path(&state, &token), text)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+async fn recover(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<String, AppError> {
+    let token = api_token(&state, &headers, &query)?;
+
+    fs::read_to_string(backup_path(&state, &token))
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "no backup".to_string()))
+}
+
+async fn feedback(
+    State(state): State<Arc<AppState>>,
+    Json(data): Json<Value>,
+) -> Result<StatusCode, AppError> {
+    let mut text = serde_json::to_string(&data).map_err(internal_error)?;
+    text.push('\n');
+
+    let _lock = state.lock.lock().await;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(state.data_dir.join(FEEDBACK_FILE))
+        .await
+        .map_err(internal_error)?;
+    file.write_all(text.as_bytes())
+        .await
+        .map_err(internal_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+async fn load_rss_list(path: &PathBuf) -> Vec<ComEntry> {
+    match fs::read_to_string(path).await {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+async fn rss_list(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+) -> Result<Json<Vec<ComEntry>>, AppError> {
+    let path = rss_list_path(&state, &language)?;
+    Ok(Json(load_rss_list(&path).await))
+}
+
+// Add or update the entry of the find list. It requires the api token, the anonymous clients can only read the list.
+async fn add_rss(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    Json(entry): Json<FindEntry>,
+) -> Result<StatusCode, AppError> {
+    api_token(&state, &headers, &query)?;
+
+    if entry.name.trim().is_empty() || entry.url.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "empty name or url".to_string()));
+    }
+
+    let path = rss_list_path(&state, &language)?;
+
+    let _lock = state.lock.lock().await;
+    let mut items = load_rss_list(&path).await;
+
+    let data = serde_json::to_string(&entry).map_err(internal_error)?;
+
+    // the existing entry is updated, so that the category and the verified date are kept fresh
+    let index = items.iter().position(|item| {
+        serde_json::from_str::<FindEntry>(&item.data)
+            .map(|item| url::is_same(&item.url, &entry.url))
+            .unwrap_or_default()
+    });
+
+    let status = match index {
+        Some(index) => {
+            if items[index].data == data {
+                return Ok(StatusCode::OK);
+            }
+            items[index].data = data;
+            StatusCode::OK
+        }
+        _ => {
+            items.push(ComEntry {
+                uuid: Uuid::new_v4().to_string(),
+                data,
+            });
+            StatusCode::CREATED
+        }
+    };
+
+    let text = serde_json::to_string(&items).map_err(internal_error)?;
+    fs::write(&path, text).await.map_err(internal_error)?;
+
+    Ok(status)
+}
+
+// Remove the entrys of the same url, such as the dead feeds. It requires the api token.
+async fn remove_rss(
+    State(state): State<Arc<AppState>>,
+    Path(language): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<StatusCode, AppError> {
+    api_token(&state, &headers, &query)?;
+
+    let remove_url = query
+        .get("url")
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .ok_or((StatusCode::BAD_REQUEST, "empty url".to_string()))?;
+
+    let path = rss_list_path(&state, &language)?;
+
+    let _lock = state.lock.lock().await;
+    let mut items = load_rss_list(&path).await;
+    let counts = items.len();
+
+    items.retain(|item| {
+        serde_json::from_str::<FindEntry>(&item.data)
+            .map(|item| !url::is_same(&item.url, remove_url))
+            .unwrap_or(true)
+    });
+
+    if items.len() == counts {
+        return Err((StatusCode::NOT_FOUND, "no such url".to_string()));
+    }
+
+    let text = serde_json::to_string(&items).map_err(internal_error)?;
+    fs::write(&path, text).await.map_err(internal_error)?;
+
+    Ok(StatusCode::OK)
+}
+
+// Serve the content of `latest-version.json` in the data directory as it is
+async fn latest_version(State(state): State<Arc<AppState>>) -> Result<Json<Value>, AppError> {
+    let text = fs::read_to_string(state.data_dir.join(LATEST_VERSION_FILE))
+        .await
+        .map_err(|_| (StatusCode::NOT_FOUND, "no version information".to_string()))?;
+
+    Ok(Json(serde_json::from_str(&text).map_err(internal_error)?))
+}
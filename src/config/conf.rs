@@ -47,12 +47,22 @@ pub fn all() -> data::Config {
     CONFIG.lock().unwrap().clone()
 }
 
+pub fn backup_dir() -> PathBuf {
+    let c = CONFIG.lock().unwrap();
+    if c.backup_recover.local_dir.trim().is_empty() {
+        c.backup_dir.clone()
+    } else {
+        PathBuf::from(c.backup_recover.local_dir.trim())
+    }
+}
+
 pub fn reset(mut conf: Config) {
     let mut c = CONFIG.lock().unwrap();
 
     conf.config_path = c.config_path.clone();
     conf.db_path = c.db_path.clone();
     conf.cache_dir = c.cache_dir.clone();
+    conf.backup_dir = c.backup_dir.clone();
     conf.is_first_run = c.is_first_run;
 
     *c = conf;
@@ -79,6 +89,26 @@ pub fn backup_recover() -> data::BackupRecover {
     CONFIG.lock().unwrap().backup_recover.clone()
 }
 
+pub fn cache() -> data::Cache {
+    CONFIG.lock().unwrap().cache.clone()
+}
+
+pub fn endpoint() -> data::Endpoint {
+    CONFIG.lock().unwrap().endpoint.clone()
+}
+
+pub fn webdav() -> data::WebDav {
+    CONFIG.lock().unwrap().webdav.clone()
+}
+
+pub fn account() -> data::Account {
+    CONFIG.lock().unwrap().account.clone()
+}
+
+pub fn local_api() -> data::LocalApi {
+    CONFIG.lock().unwrap().local_api.clone()
+}
+
 pub fn db_path() -> PathBuf {
     CONFIG.lock().unwrap().db_path.clone()
 }
@@ -118,6 +148,7 @@ impl Config {
         self.db_path = app_dirs.data_dir.join("rssbox.db");
         self.config_path = app_dirs.config_dir.join("rssbox.toml");
         self.cache_dir = app_dirs.data_dir.join("cache");
+        self.backup_dir = app_dirs.data_dir.join("backup");
 
         if self.appid.is_empty() {
             self.appid = super::data::appid_default();
@@ -126,6 +157,7 @@ impl Config {
         fs::create_dir_all(&app_dirs.data_dir)?;
         fs::create_dir_all(&app_dirs.config_dir)?;
         fs::create_dir_all(&self.cache_dir)?;
+        fs::create_dir_all(&self.backup_dir)?;
 
         Ok(())
     }
@@ -140,6 +172,11 @@ impl Config {
                     self.proxy = c.proxy;
                     self.sync = c.sync;
                     self.backup_recover = c.backup_recover;
+                    self.cache = c.cache;
+                    self.endpoint = c.endpoint;
+                    self.webdav = c.webdav;
+                    self.account = c.account;
+                    self.local_api = c.local_api;
                     Ok(())
                 }
                 Err(_) => {
@@ -167,3 +204,44 @@ impl Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_load_and_save() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rssbox-config-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir)?;
+
+        let mut conf = Config {
+            config_path: dir.join("rssbox.toml"),
+            ..Default::default()
+        };
+        conf.local_api.enabled = true;
+        conf.local_api.token = "token-1".to_string();
+        conf.save()?;
+
+        let mut loaded = Config {
+            config_path: conf.config_path.clone(),
+            ..Default::default()
+        };
+        loaded.load()?;
+        assert!(!loaded.is_first_run);
+        assert!(loaded.local_api.enabled);
+        assert_eq!(loaded.local_api.token, "token-1");
+
+        // saving the loaded config keeps the section
+        loaded.save()?;
+        let mut reloaded = Config {
+            config_path: conf.config_path.clone(),
+            ..Default::default()
+        };
+        reloaded.load()?;
+        assert!(reloaded.local_api.enabled);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -13,6 +13,9 @@ pub struct Config {
     #[serde(skip)]
     pub cache_dir: PathBuf,
 
+    #[serde(skip)]
+    pub backup_dir: PathBuf,
+
     #[serde(skip)]
     pub is_first_run: bool,
 
@@ -29,6 +32,21 @@ pub struct Config {
 
     #[serde(default)]
     pub backup_recover: BackupRecover,
+
+    #[serde(default)]
+    pub cache: Cache,
+
+    #[serde(default)]
+    pub endpoint: Endpoint,
+
+    #[serde(default)]
+    pub webdav: WebDav,
+
+    #[serde(default)]
+    pub account: Account,
+
+    #[serde(default)]
+    pub local_api: LocalApi,
 }
 
 pub fn appid_default() -> String {
@@ -109,10 +127,274 @@ impl Default for Reading {
     }
 }
 
-#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BackupRecover {
     pub api_token: String,
     pub favorite: bool,
     pub rss: bool,
     pub setting: bool,
+
+    #[serde(default)]
+    pub read_state: bool,
+
+    #[serde(default)]
+    pub blacklist: bool,
+
+    #[serde(default)]
+    pub trash: bool,
+
+    #[serde(default)]
+    pub entrys: bool,
+
+    // remove the local subscriptions which are not in the backup when recovering
+    #[serde(default)]
+    pub remove_rss: bool,
+
+    // empty means the default backup directory
+    #[serde(default)]
+    pub local_dir: String,
+
+    // empty means the backups are not encrypted
+    #[serde(default)]
+    pub passphrase: String,
+
+    // `Off`, `Daily` or `Weekly`
+    #[serde(default = "auto_backup_default")]
+    pub auto_backup: String,
+
+    // `Local`, `Remote` or `WebDAV`
+    #[serde(default = "auto_backup_destination_default")]
+    pub auto_backup_destination: String,
+
+    // the counts of the latest auto backups to keep
+    #[serde(default = "auto_backup_keep_default")]
+    pub auto_backup_keep: u32,
+
+    #[serde(default)]
+    pub last_auto_backup_time: i64,
+
+    // empty means the last auto backup is successful
+    #[serde(default)]
+    pub last_auto_backup_error: String,
+}
+
+fn auto_backup_default() -> String {
+    "Off".to_string()
+}
+
+fn auto_backup_destination_default() -> String {
+    "Local".to_string()
+}
+
+fn auto_backup_keep_default() -> u32 {
+    7
+}
+
+impl Default for BackupRecover {
+    fn default() -> Self {
+        Self {
+            api_token: String::default(),
+            favorite: false,
+            rss: false,
+            setting: false,
+            read_state: false,
+            blacklist: false,
+            trash: false,
+            entrys: false,
+            remove_rss: false,
+            local_dir: String::default(),
+            passphrase: String::default(),
+            auto_backup: auto_backup_default(),
+            auto_backup_destination: auto_backup_destination_default(),
+            auto_backup_keep: auto_backup_keep_default(),
+            last_auto_backup_time: 0,
+            last_auto_backup_error: String::default(),
+        }
+    }
+}
+
+impl BackupRecover {
+    // seconds, `None` means the auto backup is off
+    pub fn auto_backup_interval(&self) -> Option<i64> {
+        match self.auto_backup.as_str() {
+            "Daily" => Some(24 * 60 * 60),
+            "Weekly" => Some(7 * 24 * 60 * 60),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cache {
+    pub trash_expire_days: i64,
+    pub trash_max_rows: i64,
+
+    #[serde(default = "trash_recoverable_days_default")]
+    pub trash_recoverable_days: i64,
+
+    #[serde(default = "maintenance_interval_days_default")]
+    pub maintenance_interval_days: i64,
+
+    #[serde(default)]
+    pub last_maintenance_time: i64,
+}
+
+fn trash_recoverable_days_default() -> i64 {
+    7
+}
+
+fn maintenance_interval_days_default() -> i64 {
+    7
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            trash_expire_days: 90,
+            trash_max_rows: 50000,
+            trash_recoverable_days: trash_recoverable_days_default(),
+            maintenance_interval_days: maintenance_interval_days_default(),
+            last_maintenance_time: 0,
+        }
+    }
+}
+
+// The backups are saved in the directory of the url, such as `https://dav.example.com/remote.php/dav/files/user/rssbox`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct WebDav {
+    pub url: String,
+    pub username: String,
+
+    // the app password of Nextcloud, not the login password
+    pub password: String,
+
+    pub use_http_proxy: bool,
+    pub use_socks5_proxy: bool,
+}
+
+// The subscriptions, entries and their states are synchronized with the server of the account.
+// An empty kind means the feeds are fetched directly.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Account {
+    pub kind: String,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+
+    pub use_http_proxy: bool,
+    pub use_socks5_proxy: bool,
+
+    // the id of the last pulled item of the Fever api
+    #[serde(default)]
+    pub since_id: i64,
+}
+
+// The REST api of the desktop app for the automation. It only listens on the localhost,
+// and the token is generated if it is empty when the api starts.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LocalApi {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for LocalApi {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8005,
+            token: String::default(),
+        }
+    }
+}
+
+pub const ENDPOINT_ROOT_URL_DEFAULT: &str = "https://heng30.xyz/apisvr";
+
+// All the api urls are derived from the root url, so that a self-hosted
+// server only needs to implement the same paths.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Endpoint {
+    pub root_url: String,
+}
+
+impl Default for Endpoint {
+    fn default() -> Self {
+        Self {
+            root_url: ENDPOINT_ROOT_URL_DEFAULT.to_string(),
+        }
+    }
+}
+
+impl Endpoint {
+    fn url(&self, path: &str) -> String {
+        let root_url = self.root_url.trim().trim_end_matches('/');
+        let root_url = if root_url.is_empty() {
+            ENDPOINT_ROOT_URL_DEFAULT
+        } else {
+            root_url
+        };
+
+        format!("{root_url}/{path}")
+    }
+
+    pub fn backup_url(&self) -> String {
+        self.url("rssbox/android/backup")
+    }
+
+    pub fn recover_url(&self) -> String {
+        self.url("rssbox/android/recover")
+    }
+
+    pub fn feedback_url(&self) -> String {
+        self.url("rssbox/android/feedback")
+    }
+
+    pub fn latest_version_url(&self) -> String {
+        self.url("latest/version?q=rssbox-android")
+    }
+
+    // language: `cn` or `en`
+    pub fn rss_list_url(&self, language: &str) -> String {
+        self.url(&format!("rssbox/rss/list/{language}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        let endpoint = Endpoint {
+            root_url: "http://192.168.1.2:8004/".to_string(),
+        };
+        assert_eq!(
+            endpoint.backup_url(),
+            "http://192.168.1.2:8004/rssbox/android/backup"
+        );
+        assert_eq!(
+            endpoint.rss_list_url("cn"),
+            "http://192.168.1.2:8004/rssbox/rss/list/cn"
+        );
+
+        let endpoint = Endpoint {
+            root_url: " ".to_string(),
+        };
+        assert_eq!(
+            endpoint.recover_url(),
+            format!("{ENDPOINT_ROOT_URL_DEFAULT}/rssbox/android/recover")
+        );
+    }
+
+    #[test]
+    fn test_auto_backup_interval() {
+        let mut conf = BackupRecover::default();
+        assert_eq!(conf.auto_backup_interval(), None);
+
+        conf.auto_backup = "Daily".to_string();
+        assert_eq!(conf.auto_backup_interval(), Some(86400));
+
+        conf.auto_backup = "Weekly".to_string();
+        assert_eq!(conf.auto_backup_interval(), Some(604800));
+    }
 }
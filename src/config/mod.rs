@@ -2,6 +2,7 @@ mod conf;
 mod data;
 
 pub use conf::{
-    all, appid, backup_recover, db_path, init, is_first_run, proxy, reading, reset, save, sync, ui,
+    account, all, appid, backup_dir, backup_recover, cache, db_path, endpoint, init, is_first_run,
+    local_api, proxy, reading, reset, save, sync, ui, webdav,
 };
-pub use data::Config;
+pub use data::{Account, BackupRecover, Cache, Config};
@@ -1,120 +1,141 @@
-use super::pool;
 use anyhow::Result;
-use sqlx::Row;
+use sqlx::{Pool, Row, Sqlite};
 
-pub async fn new() -> Result<()> {
+pub async fn new(pool: &Pool<Sqlite>) -> Result<()> {
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS blacklist (
              id INTEGER PRIMARY KEY,
              md5 TEXT NOT NULL UNIQUE
              )",
     )
-    .execute(&pool())
+    .execute(pool)
     .await?;
 
     Ok(())
 }
 
-pub async fn insert(md5: &str) -> Result<()> {
+pub async fn insert(pool: &Pool<Sqlite>, md5: &str) -> Result<()> {
     sqlx::query("INSERT INTO blacklist (md5) VALUES (?)")
         .bind(md5)
-        .execute(&pool())
+        .execute(pool)
         .await?;
     Ok(())
 }
 
-pub async fn delete(md5: &str) -> Result<()> {
+pub async fn delete(pool: &Pool<Sqlite>, md5: &str) -> Result<()> {
     sqlx::query("DELETE FROM blacklist WHERE md5=?")
         .bind(md5)
-        .execute(&pool())
+        .execute(pool)
         .await?;
     Ok(())
 }
 
-pub async fn select(md5: &str) -> Result<String> {
+pub async fn delete_all(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("DELETE FROM blacklist").execute(pool).await?;
+    Ok(())
+}
+
+pub async fn select(pool: &Pool<Sqlite>, md5: &str) -> Result<String> {
     let row = sqlx::query("SELECT * FROM blacklist WHERE md5=?")
         .bind(md5)
-        .fetch_one(&pool())
+        .fetch_one(pool)
         .await?;
 
     Ok(row.try_get("md5")?)
 }
 
-pub async fn is_exist(md5: &str) -> Result<()> {
-    select(md5).await?;
+pub async fn select_all(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT md5 FROM blacklist ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().map(|item| item.0).collect())
+}
+
+pub async fn is_exist(pool: &Pool<Sqlite>, md5: &str) -> Result<()> {
+    select(pool, md5).await?;
     Ok(())
 }
 
+pub async fn row_count(pool: &Pool<Sqlite>) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM blacklist")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::db;
-    use std::sync::Mutex;
-
-    static MTX: Mutex<()> = Mutex::new(());
-    const DB_PATH: &str = "/tmp/rssbox-blacklist-test.db";
 
     #[tokio::test]
     async fn test_table_new() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await
+        let pool = db::memory_pool().await?;
+        new(&pool).await
     }
 
     #[tokio::test]
     async fn test_delete_all() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await
     }
 
     #[tokio::test]
     async fn test_insert() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        insert("md5-1").await?;
-        insert("md5-2").await
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1").await?;
+        insert(&pool, "md5-2").await
     }
 
     #[tokio::test]
     async fn test_select_one() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        assert!(select("md5-1").await.is_err());
-
-        insert("md5-1").await?;
-        assert_eq!(select("md5-1").await?, "md5-1");
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        assert!(select(&pool, "md5-1").await.is_err());
+
+        insert(&pool, "md5-1").await?;
+        assert_eq!(select(&pool, "md5-1").await?, "md5-1");
         Ok(())
     }
 
     #[tokio::test]
     async fn test_is_exist() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        insert("md5-1").await?;
-
-        assert!(is_exist("md5-0").await.is_err());
-        assert!(is_exist("md5-1").await.is_ok());
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1").await?;
+
+        assert!(is_exist(&pool, "md5-0").await.is_err());
+        assert!(is_exist(&pool, "md5-1").await.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_select_all() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        insert(&pool, "md5-1").await?;
+        insert(&pool, "md5-2").await?;
+
+        assert_eq!(select_all(&pool).await?, vec!["md5-1", "md5-2"]);
         Ok(())
     }
 
     #[tokio::test]
     async fn test_row_count() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        assert_eq!(row_count().await.unwrap(), 0);
-
-        insert("md5-1").await?;
-        assert_eq!(row_count().await.unwrap(), 1);
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        assert_eq!(row_count(&pool).await.unwrap(), 0);
+
+        insert(&pool, "md5-1").await?;
+        assert_eq!(row_count(&pool).await.unwrap(), 1);
         Ok(())
     }
 }
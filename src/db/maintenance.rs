@@ -0,0 +1,166 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TableStat {
+    pub name: String,
+    pub rows: i64,
+    pub bytes: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DbStat {
+    // the bytes of the database file, including the free pages
+    pub total_bytes: i64,
+
+    // the bytes which can be reclaimed by `VACUUM`
+    pub free_bytes: i64,
+
+    pub tables: Vec<TableStat>,
+}
+
+pub async fn table_names(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+    let names: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(names.into_iter().map(|item| item.0).collect())
+}
+
+pub async fn row_count(pool: &Pool<Sqlite>, table_name: &str) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", table_name))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count.0)
+}
+
+// The `dbstat` virtual table is only available when sqlite is built with `SQLITE_ENABLE_DBSTAT_VTAB`.
+// The size includes the pages of the table and its indexes.
+pub async fn table_size(pool: &Pool<Sqlite>, table_name: &str) -> Result<i64> {
+    let size: (i64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(pgsize), 0) FROM dbstat WHERE name=?
+             OR name IN (SELECT name FROM sqlite_master WHERE type='index' AND tbl_name=?)",
+    )
+    .bind(table_name)
+    .bind(table_name)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(size.0)
+}
+
+pub async fn page_size(pool: &Pool<Sqlite>) -> Result<i64> {
+    let size: (i64,) = sqlx::query_as("PRAGMA page_size").fetch_one(pool).await?;
+    Ok(size.0)
+}
+
+pub async fn page_count(pool: &Pool<Sqlite>) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as("PRAGMA page_count").fetch_one(pool).await?;
+    Ok(count.0)
+}
+
+pub async fn freelist_count(pool: &Pool<Sqlite>) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as("PRAGMA freelist_count")
+        .fetch_one(pool)
+        .await?;
+    Ok(count.0)
+}
+
+pub async fn stat(pool: &Pool<Sqlite>) -> Result<DbStat> {
+    let page_size = page_size(pool).await?;
+    let mut tables = vec![];
+
+    for name in table_names(pool).await?.into_iter() {
+        let rows = row_count(pool, &name).await?;
+        let bytes = match table_size(pool, &name).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::debug!("{e:?}");
+                0
+            }
+        };
+
+        tables.push(TableStat { name, rows, bytes });
+    }
+
+    Ok(DbStat {
+        total_bytes: page_count(pool).await? * page_size,
+        free_bytes: freelist_count(pool).await? * page_size,
+        tables,
+    })
+}
+
+// Return an empty list if the database is fine, otherwise the problems which are found
+pub async fn integrity_check(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|item| item.0)
+        .filter(|item| item != "ok")
+        .collect())
+}
+
+pub async fn vacuum(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok(())
+}
+
+pub async fn analyze(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("ANALYZE").execute(pool).await?;
+    Ok(())
+}
+
+// Move the WAL content back to the database file, so that the file size is correct
+pub async fn checkpoint(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[tokio::test]
+    async fn test_stat() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        db::trash::new(&pool).await?;
+        db::trash::insert(&pool, "md5-1", "suuid-1", "").await?;
+
+        let stat = stat(&pool).await?;
+        assert!(stat.total_bytes > 0);
+        assert!(stat.free_bytes <= stat.total_bytes);
+
+        let trash = stat
+            .tables
+            .iter()
+            .find(|item| item.name == "trash")
+            .unwrap();
+        assert_eq!(trash.rows, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_integrity_check() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        assert!(integrity_check(&pool).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vacuum_analyze() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        vacuum(&pool).await?;
+        analyze(&pool).await?;
+        checkpoint(&pool).await
+    }
+}
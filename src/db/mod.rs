@@ -3,17 +3,28 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use sqlx::migrate::MigrateDatabase;
 use sqlx::{
-    sqlite::{Sqlite, SqlitePoolOptions},
+    sqlite::{
+        Sqlite, SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous,
+    },
     Pool,
 };
-use std::sync::Mutex;
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 pub mod blacklist;
 pub mod entry;
+pub mod maintenance;
+pub mod repo;
 pub mod rss;
 pub mod trash;
 
+pub use repo::{BlacklistRepo, EntryRepo, FeedRepo, Repository, SqliteRepository, TrashRepo};
+
 const MAX_CONNECTIONS: u32 = 3;
+const BUSY_TIMEOUT: u64 = 5;
 
 #[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
 pub struct ComEntry {
@@ -22,45 +33,91 @@ pub struct ComEntry {
 }
 
 static POOL: Lazy<Mutex<Option<Pool<Sqlite>>>> = Lazy::new(|| Mutex::new(None));
+static REPO: Lazy<Mutex<Option<Arc<dyn Repository>>>> = Lazy::new(|| Mutex::new(None));
 
-fn pool() -> Pool<Sqlite> {
+// The pool of the database file. Only the sqlite specific operations, such as maintenance, should use it.
+pub fn pool() -> Pool<Sqlite> {
     POOL.lock().unwrap().clone().unwrap()
 }
 
-async fn create_db(db_path: &str) -> Result<()> {
+pub fn repo() -> Arc<dyn Repository> {
+    REPO.lock().unwrap().clone().expect("db is not initialized")
+}
+
+pub fn set_repo(repo: Arc<dyn Repository>) {
+    *REPO.lock().unwrap() = Some(repo);
+}
+
+async fn create_db(db_path: &str) -> Result<Pool<Sqlite>> {
     Sqlite::create_database(db_path).await?;
 
+    // WAL lets the ui read while syncing writes the entrys
+    let options = SqliteConnectOptions::from_str(&format!("sqlite:{}", db_path))?
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(Duration::from_secs(BUSY_TIMEOUT))
+        .pragma("temp_store", "memory");
+
     let pool = SqlitePoolOptions::new()
         .max_connections(MAX_CONNECTIONS)
-        .connect(&format!("sqlite:{}", db_path))
+        .connect_with(options)
         .await?;
 
-    *POOL.lock().unwrap() = Some(pool);
+    Ok(pool)
+}
 
-    Ok(())
+// Every connection of `sqlite::memory:` opens a new database, so keep only one connection alive
+pub async fn memory_pool() -> Result<Pool<Sqlite>> {
+    let pool = SqlitePoolOptions::new()
+        .min_connections(1)
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await?;
+
+    Ok(pool)
 }
 
 pub async fn init(db_path: &str) {
-    create_db(db_path).await.expect("create db");
-    rss::new().await.expect("rss table failed");
-    trash::new().await.expect("trash table failed");
-    blacklist::new().await.expect("blacklist table failed");
+    let pool = create_db(db_path).await.expect("create db");
+    let repo = SqliteRepository::new(pool.clone());
+    repo.init().await.expect("create tables failed");
+
+    *POOL.lock().unwrap() = Some(pool);
+    set_repo(Arc::new(repo));
 }
 
 #[allow(dead_code)]
-pub async fn is_table_exist(table_name: &str) -> Result<()> {
+pub async fn is_table_exist(pool: &Pool<Sqlite>, table_name: &str) -> Result<()> {
     sqlx::query("SELECT name FROM sqlite_master WHERE type='table' AND name=?")
         .bind(table_name)
-        .fetch_one(&pool())
+        .fetch_one(pool)
         .await?;
 
     Ok(())
 }
 
+pub async fn is_column_exist(
+    pool: &Pool<Sqlite>,
+    table_name: &str,
+    column_name: &str,
+) -> Result<()> {
+    sqlx::query(&format!(
+        "SELECT name FROM pragma_table_info('{}') WHERE name=?",
+        table_name
+    ))
+    .bind(column_name)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(())
+}
+
 #[allow(dead_code)]
-pub async fn drop_table(table_name: &str) -> Result<()> {
+pub async fn drop_table(pool: &Pool<Sqlite>, table_name: &str) -> Result<()> {
     sqlx::query(&format!("DROP TABLE {}", table_name))
-        .execute(&pool())
+        .execute(pool)
         .await?;
 
     Ok(())
@@ -69,30 +126,31 @@ pub async fn drop_table(table_name: &str) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Mutex;
-
-    static MTX: Mutex<()> = Mutex::new(());
-    const DB_PATH: &str = "/tmp/rssbox-test.db";
 
     #[tokio::test]
     async fn test_db_is_table_exist() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
+        let pool = memory_pool().await?;
+        trash::new(&pool).await?;
+        assert!(is_table_exist(&pool, "hello").await.is_err());
+        assert!(is_table_exist(&pool, "trash").await.is_ok());
+        Ok(())
+    }
 
-        init(DB_PATH).await;
-        trash::new().await?;
-        assert!(is_table_exist("hello").await.is_err());
-        assert!(is_table_exist("trash").await.is_ok());
+    #[tokio::test]
+    async fn test_db_is_column_exist() -> Result<()> {
+        let pool = memory_pool().await?;
+        trash::new(&pool).await?;
+        assert!(is_column_exist(&pool, "trash", "hello").await.is_err());
+        assert!(is_column_exist(&pool, "trash", "delete_time").await.is_ok());
         Ok(())
     }
 
     #[tokio::test]
     async fn test_db_drop_table() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-
-        init(DB_PATH).await;
-        trash::new().await?;
-        assert!(drop_table("hello").await.is_err());
-        assert!(drop_table("trash").await.is_ok());
+        let pool = memory_pool().await?;
+        trash::new(&pool).await?;
+        assert!(drop_table(&pool, "hello").await.is_err());
+        assert!(drop_table(&pool, "trash").await.is_ok());
         Ok(())
     }
 }
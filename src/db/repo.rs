@@ -0,0 +1,298 @@
+use super::{blacklist, entry, memory_pool, rss, trash, trash::TrashEntry, ComEntry};
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+
+#[async_trait]
+pub trait FeedRepo: Send + Sync {
+    async fn insert_rss(&self, uuid: &str, data: &str) -> Result<()>;
+
+    // insert the rss and create their entry lists in one transaction
+    async fn insert_all_rss(&self, items: &[ComEntry]) -> Result<()>;
+    async fn update_rss(&self, uuid: &str, data: &str) -> Result<()>;
+    async fn delete_rss(&self, uuid: &str) -> Result<()>;
+    async fn delete_all_rss(&self) -> Result<()>;
+    async fn select_rss(&self, uuid: &str) -> Result<ComEntry>;
+    async fn select_all_rss(&self) -> Result<Vec<ComEntry>>;
+}
+
+// Every rss has its own entry list, which is identified by `suuid`
+#[async_trait]
+pub trait EntryRepo: Send + Sync {
+    async fn new_entry_list(&self, suuid: &str) -> Result<()>;
+    async fn drop_entry_list(&self, suuid: &str) -> Result<()>;
+    async fn insert_entry(&self, suuid: &str, uuid: &str, data: &str) -> Result<()>;
+    async fn update_entry(&self, suuid: &str, uuid: &str, data: &str) -> Result<()>;
+    async fn delete_entry(&self, suuid: &str, uuid: &str) -> Result<()>;
+    async fn delete_all_entrys(&self, suuid: &str) -> Result<()>;
+    async fn select_entry(&self, suuid: &str, uuid: &str) -> Result<ComEntry>;
+    async fn select_all_entrys(&self, suuid: &str) -> Result<Vec<ComEntry>>;
+}
+
+#[async_trait]
+pub trait TrashRepo: Send + Sync {
+    async fn insert_trash(&self, md5: &str, suuid: &str, data: &str) -> Result<()>;
+    async fn delete_trash(&self, md5: &str) -> Result<()>;
+    async fn delete_all_trash(&self) -> Result<()>;
+    async fn delete_trash_older_than(&self, timestamp: i64) -> Result<u64>;
+    async fn forget_trash(&self, md5: &str) -> Result<()>;
+    async fn forget_trash_older_than(&self, timestamp: i64) -> Result<u64>;
+    async fn shrink_trash(&self, max_rows: i64) -> Result<u64>;
+    async fn select_trash(&self, md5: &str) -> Result<TrashEntry>;
+    async fn select_recoverable_trash(&self) -> Result<Vec<TrashEntry>>;
+    async fn select_all_trash(&self) -> Result<Vec<TrashEntry>>;
+    async fn restore_trash(&self, entry: &TrashEntry) -> Result<()>;
+
+    // put the entrys back to their entry lists, `data` is the serialized entry
+    async fn recover_trash(&self, items: &[TrashEntry]) -> Result<()>;
+    async fn is_exist_trash(&self, md5: &str) -> Result<()>;
+    async fn trash_row_count(&self) -> Result<i64>;
+    async fn trash_data_size(&self) -> Result<i64>;
+}
+
+#[async_trait]
+pub trait BlacklistRepo: Send + Sync {
+    async fn insert_blacklist(&self, md5: &str) -> Result<()>;
+    async fn delete_blacklist(&self, md5: &str) -> Result<()>;
+    async fn is_exist_blacklist(&self, md5: &str) -> Result<()>;
+    async fn select_all_blacklist(&self) -> Result<Vec<String>>;
+}
+
+#[async_trait]
+pub trait Repository: FeedRepo + EntryRepo + TrashRepo + BlacklistRepo {
+    // create the tables which are always needed
+    async fn init(&self) -> Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct SqliteRepository {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRepository {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    // A database which only lives in memory. It's useful for testing.
+    pub async fn memory() -> Result<Self> {
+        let repo = Self::new(memory_pool().await?);
+        repo.init().await?;
+        Ok(repo)
+    }
+
+    pub fn pool(&self) -> &Pool<Sqlite> {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl Repository for SqliteRepository {
+    async fn init(&self) -> Result<()> {
+        rss::new(&self.pool).await?;
+        trash::new(&self.pool).await?;
+        blacklist::new(&self.pool).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FeedRepo for SqliteRepository {
+    async fn insert_rss(&self, uuid: &str, data: &str) -> Result<()> {
+        rss::insert(&self.pool, uuid, data).await
+    }
+
+    async fn insert_all_rss(&self, items: &[ComEntry]) -> Result<()> {
+        rss::insert_all(&self.pool, items).await
+    }
+
+    async fn update_rss(&self, uuid: &str, data: &str) -> Result<()> {
+        rss::update(&self.pool, uuid, data).await
+    }
+
+    async fn delete_rss(&self, uuid: &str) -> Result<()> {
+        rss::delete(&self.pool, uuid).await
+    }
+
+    async fn delete_all_rss(&self) -> Result<()> {
+        rss::delete_all(&self.pool).await
+    }
+
+    async fn select_rss(&self, uuid: &str) -> Result<ComEntry> {
+        rss::select(&self.pool, uuid).await
+    }
+
+    async fn select_all_rss(&self) -> Result<Vec<ComEntry>> {
+        rss::select_all(&self.pool).await
+    }
+}
+
+#[async_trait]
+impl EntryRepo for SqliteRepository {
+    async fn new_entry_list(&self, suuid: &str) -> Result<()> {
+        entry::new(&self.pool, suuid).await
+    }
+
+    async fn drop_entry_list(&self, suuid: &str) -> Result<()> {
+        entry::drop_table(&self.pool, suuid).await
+    }
+
+    async fn insert_entry(&self, suuid: &str, uuid: &str, data: &str) -> Result<()> {
+        entry::insert(&self.pool, suuid, uuid, data).await
+    }
+
+    async fn update_entry(&self, suuid: &str, uuid: &str, data: &str) -> Result<()> {
+        entry::update(&self.pool, suuid, uuid, data).await
+    }
+
+    async fn delete_entry(&self, suuid: &str, uuid: &str) -> Result<()> {
+        entry::delete(&self.pool, suuid, uuid).await
+    }
+
+    async fn delete_all_entrys(&self, suuid: &str) -> Result<()> {
+        entry::delete_all(&self.pool, suuid).await
+    }
+
+    async fn select_entry(&self, suuid: &str, uuid: &str) -> Result<ComEntry> {
+        entry::select(&self.pool, suuid, uuid).await
+    }
+
+    async fn select_all_entrys(&self, suuid: &str) -> Result<Vec<ComEntry>> {
+        entry::select_all(&self.pool, suuid).await
+    }
+}
+
+#[async_trait]
+impl TrashRepo for SqliteRepository {
+    async fn insert_trash(&self, md5: &str, suuid: &str, data: &str) -> Result<()> {
+        trash::insert(&self.pool, md5, suuid, data).await
+    }
+
+    async fn delete_trash(&self, md5: &str) -> Result<()> {
+        trash::delete(&self.pool, md5).await
+    }
+
+    async fn delete_all_trash(&self) -> Result<()> {
+        trash::delete_all(&self.pool).await
+    }
+
+    async fn delete_trash_older_than(&self, timestamp: i64) -> Result<u64> {
+        trash::delete_older_than(&self.pool, timestamp).await
+    }
+
+    async fn forget_trash(&self, md5: &str) -> Result<()> {
+        trash::forget(&self.pool, md5).await
+    }
+
+    async fn forget_trash_older_than(&self, timestamp: i64) -> Result<u64> {
+        trash::forget_older_than(&self.pool, timestamp).await
+    }
+
+    async fn shrink_trash(&self, max_rows: i64) -> Result<u64> {
+        trash::shrink(&self.pool, max_rows).await
+    }
+
+    async fn select_trash(&self, md5: &str) -> Result<TrashEntry> {
+        trash::select_entry(&self.pool, md5).await
+    }
+
+    async fn select_recoverable_trash(&self) -> Result<Vec<TrashEntry>> {
+        trash::select_recoverable(&self.pool).await
+    }
+
+    async fn select_all_trash(&self) -> Result<Vec<TrashEntry>> {
+        trash::select_all(&self.pool).await
+    }
+
+    async fn restore_trash(&self, entry: &TrashEntry) -> Result<()> {
+        trash::restore(&self.pool, entry).await
+    }
+
+    async fn recover_trash(&self, items: &[TrashEntry]) -> Result<()> {
+        trash::recover(&self.pool, items).await
+    }
+
+    async fn is_exist_trash(&self, md5: &str) -> Result<()> {
+        trash::is_exist(&self.pool, md5).await
+    }
+
+    async fn trash_row_count(&self) -> Result<i64> {
+        trash::row_count(&self.pool).await
+    }
+
+    async fn trash_data_size(&self) -> Result<i64> {
+        trash::data_size(&self.pool).await
+    }
+}
+
+#[async_trait]
+impl BlacklistRepo for SqliteRepository {
+    async fn insert_blacklist(&self, md5: &str) -> Result<()> {
+        blacklist::insert(&self.pool, md5).await
+    }
+
+    async fn delete_blacklist(&self, md5: &str) -> Result<()> {
+        blacklist::delete(&self.pool, md5).await
+    }
+
+    async fn is_exist_blacklist(&self, md5: &str) -> Result<()> {
+        blacklist::is_exist(&self.pool, md5).await
+    }
+
+    async fn select_all_blacklist(&self) -> Result<Vec<String>> {
+        blacklist::select_all(&self.pool).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_memory_isolated() -> Result<()> {
+        let repo_1 = SqliteRepository::memory().await?;
+        let repo_2 = SqliteRepository::memory().await?;
+
+        repo_1.insert_rss("uuid-1", "data-1").await?;
+        assert_eq!(repo_1.select_all_rss().await?.len(), 1);
+        assert!(repo_2.select_all_rss().await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_entry_list() -> Result<()> {
+        let repo: Arc<dyn Repository> = Arc::new(SqliteRepository::memory().await?);
+
+        repo.new_entry_list("suuid-1").await?;
+        repo.insert_entry("suuid-1", "uuid-1", "data-1").await?;
+        repo.update_entry("suuid-1", "uuid-1", "data-1-1").await?;
+        assert_eq!(
+            repo.select_entry("suuid-1", "uuid-1").await?.data,
+            "data-1-1"
+        );
+
+        repo.delete_entry("suuid-1", "uuid-1").await?;
+        assert!(repo.select_all_entrys("suuid-1").await?.is_empty());
+
+        repo.drop_entry_list("suuid-1").await?;
+        assert!(repo.select_all_entrys("suuid-1").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_trash_and_blacklist() -> Result<()> {
+        let repo: Arc<dyn Repository> = Arc::new(SqliteRepository::memory().await?);
+
+        repo.insert_trash("md5-1", "suuid-1", "data-1").await?;
+        assert!(repo.is_exist_trash("md5-1").await.is_ok());
+        assert_eq!(repo.select_recoverable_trash().await?.len(), 1);
+        assert_eq!(repo.trash_row_count().await?, 1);
+
+        repo.insert_blacklist("md5-1").await?;
+        assert!(repo.is_exist_blacklist("md5-1").await.is_ok());
+        repo.delete_blacklist("md5-1").await?;
+        assert!(repo.is_exist_blacklist("md5-1").await.is_err());
+        Ok(())
+    }
+}
@@ -1,8 +1,9 @@
-use super::{pool, ComEntry};
+use super::{entry, ComEntry};
 use crate::slint_generatedAppWindow::{RssConfig as UIRssConfig, RssEntry as UIRssEntry};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use slint::{ModelRc, VecModel};
+use sqlx::{Pool, Sqlite};
 
 fn feed_format_default() -> String {
     "AUTO".to_string()
@@ -20,6 +21,14 @@ pub struct RssConfig {
 
     #[serde(default = "feed_format_default")]
     pub feed_format: String,
+
+    // the category path which comes from the OPML outlines, such as `Tech/Rust`
+    #[serde(default)]
+    pub category: String,
+
+    // the feed id in the server of the account, empty means the feed is fetched directly
+    #[serde(default)]
+    pub remote_id: String,
 }
 
 impl From<UIRssConfig> for RssConfig {
@@ -33,6 +42,8 @@ impl From<UIRssConfig> for RssConfig {
             use_socks5_proxy: conf.use_socks5_proxy,
             is_favorite: conf.is_favorite,
             feed_format: conf.feed_format.into(),
+            category: conf.category.into(),
+            remote_id: conf.remote_id.into(),
         }
     }
 }
@@ -48,13 +59,15 @@ impl From<RssConfig> for UIRssConfig {
             use_socks5_proxy: conf.use_socks5_proxy,
             is_favorite: conf.is_favorite,
             feed_format: conf.feed_format.into(),
+            category: conf.category.into(),
+            remote_id: conf.remote_id.into(),
             entry: ModelRc::new(VecModel::<UIRssEntry>::default()),
             ..Default::default()
         }
     }
 }
 
-pub async fn new() -> Result<()> {
+pub async fn new(pool: &Pool<Sqlite>) -> Result<()> {
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS rss (
              id INTEGER PRIMARY KEY,
@@ -62,64 +75,84 @@ pub async fn new() -> Result<()> {
              data TEXT NOT NULL
              )",
     )
-    .execute(&pool())
+    .execute(pool)
     .await?;
 
     Ok(())
 }
 
-pub async fn delete(uuid: &str) -> Result<()> {
+pub async fn delete(pool: &Pool<Sqlite>, uuid: &str) -> Result<()> {
     sqlx::query("DELETE FROM rss WHERE uuid=?")
         .bind(uuid)
-        .execute(&pool())
+        .execute(pool)
         .await?;
     Ok(())
 }
 
 #[allow(dead_code)]
-pub async fn delete_all() -> Result<()> {
-    sqlx::query("DELETE FROM rss").execute(&pool()).await?;
+pub async fn delete_all(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("DELETE FROM rss").execute(pool).await?;
     Ok(())
 }
 
-pub async fn insert(uuid: &str, data: &str) -> Result<()> {
+pub async fn insert(pool: &Pool<Sqlite>, uuid: &str, data: &str) -> Result<()> {
     sqlx::query("INSERT INTO rss (uuid, data) VALUES (?, ?)")
         .bind(uuid)
         .bind(data)
-        .execute(&pool())
+        .execute(pool)
         .await?;
     Ok(())
 }
 
-pub async fn update(uuid: &str, data: &str) -> Result<()> {
+// Insert the rss and create their entry lists in one transaction, nothing is inserted if one of them fails
+pub async fn insert_all(pool: &Pool<Sqlite>, items: &[ComEntry]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for item in items.iter() {
+        sqlx::query("INSERT INTO rss (uuid, data) VALUES (?, ?)")
+            .bind(&item.uuid)
+            .bind(&item.data)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(&entry::new_table_sql(&item.uuid))
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+pub async fn update(pool: &Pool<Sqlite>, uuid: &str, data: &str) -> Result<()> {
     sqlx::query("UPDATE rss SET data=? WHERE uuid=?")
         .bind(data)
         .bind(uuid)
-        .execute(&pool())
+        .execute(pool)
         .await?;
 
     Ok(())
 }
 
 #[allow(dead_code)]
-pub async fn select(uuid: &str) -> Result<ComEntry> {
+pub async fn select(pool: &Pool<Sqlite>, uuid: &str) -> Result<ComEntry> {
     Ok(
         sqlx::query_as::<_, ComEntry>("SELECT * FROM rss WHERE uuid=?")
             .bind(uuid)
-            .fetch_one(&pool())
+            .fetch_one(pool)
             .await?,
     )
 }
 
-pub async fn select_all() -> Result<Vec<ComEntry>> {
+pub async fn select_all(pool: &Pool<Sqlite>) -> Result<Vec<ComEntry>> {
     Ok(sqlx::query_as::<_, ComEntry>("SELECT * FROM rss")
-        .fetch_all(&pool())
+        .fetch_all(pool)
         .await?)
 }
 
 #[allow(dead_code)]
-pub async fn is_exist(uuid: &str) -> Result<()> {
-    select(uuid).await?;
+pub async fn is_exist(pool: &Pool<Sqlite>, uuid: &str) -> Result<()> {
+    select(pool, uuid).await?;
     Ok(())
 }
 
@@ -127,80 +160,106 @@ pub async fn is_exist(uuid: &str) -> Result<()> {
 mod tests {
     use super::*;
     use crate::db;
-    use std::sync::Mutex;
-
-    static MTX: Mutex<()> = Mutex::new(());
-    const DB_PATH: &str = "/tmp/rssbox-rss-test.db";
 
     #[tokio::test]
     async fn test_table_new() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
         Ok(())
     }
 
     #[tokio::test]
     async fn test_delete_all() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
         Ok(())
     }
 
     #[tokio::test]
     async fn test_delete_one() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
 
-        delete_all().await?;
-        insert("uuid-1", "data-1").await?;
-        delete("uuid-1").await?;
+        delete_all(&pool).await?;
+        insert(&pool, "uuid-1", "data-1").await?;
+        delete(&pool, "uuid-1").await?;
         Ok(())
     }
 
     #[tokio::test]
     async fn test_insert() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
 
-        insert("uuid-1", "data-1").await?;
-        insert("uuid-2", "data-2").await?;
+        insert(&pool, "uuid-1", "data-1").await?;
+        insert(&pool, "uuid-2", "data-2").await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_all() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+
+        let items = vec![
+            ComEntry {
+                uuid: "uuid-1".to_string(),
+                data: "data-1".to_string(),
+            },
+            ComEntry {
+                uuid: "uuid-2".to_string(),
+                data: "data-2".to_string(),
+            },
+        ];
+        insert_all(&pool, &items).await?;
+        assert_eq!(select_all(&pool).await?.len(), 2);
+        assert!(entry::select_all(&pool, "uuid-1").await?.is_empty());
+
+        // the duplicated uuid rolls back the whole batch
+        let items = vec![
+            ComEntry {
+                uuid: "uuid-3".to_string(),
+                data: "data-3".to_string(),
+            },
+            ComEntry {
+                uuid: "uuid-1".to_string(),
+                data: "data-1".to_string(),
+            },
+        ];
+        assert!(insert_all(&pool, &items).await.is_err());
+        assert_eq!(select_all(&pool).await?.len(), 2);
 
         Ok(())
     }
 
     #[tokio::test]
     async fn test_update() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
 
-        insert("uuid-1", "data-1").await?;
-        update("uuid-1", "data-1-1").await?;
+        insert(&pool, "uuid-1", "data-1").await?;
+        update(&pool, "uuid-1", "data-1-1").await?;
 
-        assert_eq!(select("uuid-1").await?.data, "data-1-1".to_string());
+        assert_eq!(select(&pool, "uuid-1").await?.data, "data-1-1".to_string());
 
         Ok(())
     }
 
     #[tokio::test]
     async fn test_select_one() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
 
-        assert!(select("uuid-1").await.is_err());
+        assert!(select(&pool, "uuid-1").await.is_err());
 
-        insert("uuid-1", "data-1").await?;
-        let item = select("uuid-1").await?;
+        insert(&pool, "uuid-1", "data-1").await?;
+        let item = select(&pool, "uuid-1").await?;
         assert_eq!(item.uuid, "uuid-1");
         assert_eq!(item.data, "data-1");
         Ok(())
@@ -208,16 +267,14 @@ mod tests {
 
     #[tokio::test]
     async fn test_select_all() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
 
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
+        insert(&pool, "uuid-1", "data-1").await?;
+        insert(&pool, "uuid-2", "data-2").await?;
 
-        insert("uuid-1", "data-1").await?;
-        insert("uuid-2", "data-2").await?;
-
-        let v = select_all().await?;
+        let v = select_all(&pool).await?;
         assert_eq!(v[0].uuid, "uuid-1");
         assert_eq!(v[0].data, "data-1");
         assert_eq!(v[1].uuid, "uuid-2");
@@ -227,14 +284,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_is_exist() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        insert("uuid-1", "data-1").await?;
-
-        assert!(is_exist("uuid-0").await.is_err());
-        assert!(is_exist("uuid-1").await.is_ok());
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "uuid-1", "data-1").await?;
+
+        assert!(is_exist(&pool, "uuid-0").await.is_err());
+        assert!(is_exist(&pool, "uuid-1").await.is_ok());
         Ok(())
     }
 }
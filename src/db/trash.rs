@@ -1,125 +1,432 @@
-use super::pool;
+use super::entry::{self, RssEntry};
 use anyhow::Result;
-use sqlx::Row;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
 
-pub async fn new() -> Result<()> {
+#[derive(Serialize, Deserialize, Debug, Clone, sqlx::FromRow)]
+pub struct TrashEntry {
+    pub md5: String,
+    pub suuid: String,
+    pub data: String,
+    pub delete_time: i64,
+}
+
+pub async fn new(pool: &Pool<Sqlite>) -> Result<()> {
     sqlx::query(
         "CREATE TABLE IF NOT EXISTS trash (
              id INTEGER PRIMARY KEY,
-             md5 TEXT NOT NULL UNIQUE
+             md5 TEXT NOT NULL UNIQUE,
+             suuid TEXT NOT NULL DEFAULT '',
+             data TEXT NOT NULL DEFAULT '',
+             delete_time INTEGER NOT NULL DEFAULT 0
              )",
     )
-    .execute(&pool())
+    .execute(pool)
     .await?;
 
+    migrate(pool).await
+}
+
+// Old versions only store the md5 of the url. Treat those rows as deleted right now,
+// so that they won't be expired immediately after upgrading.
+async fn migrate(pool: &Pool<Sqlite>) -> Result<()> {
+    if super::is_column_exist(pool, "trash", "suuid")
+        .await
+        .is_err()
+    {
+        sqlx::query("ALTER TABLE trash ADD COLUMN suuid TEXT NOT NULL DEFAULT ''")
+            .execute(pool)
+            .await?;
+    }
+
+    if super::is_column_exist(pool, "trash", "data").await.is_err() {
+        sqlx::query("ALTER TABLE trash ADD COLUMN data TEXT NOT NULL DEFAULT ''")
+            .execute(pool)
+            .await?;
+    }
+
+    if super::is_column_exist(pool, "trash", "delete_time")
+        .await
+        .is_err()
+    {
+        sqlx::query("ALTER TABLE trash ADD COLUMN delete_time INTEGER NOT NULL DEFAULT 0")
+            .execute(pool)
+            .await?;
+
+        sqlx::query("UPDATE trash SET delete_time=? WHERE delete_time=0")
+            .bind(Utc::now().timestamp())
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// `data` is the serialized entry which can be restored. Empty means unrecoverable.
+pub async fn insert(pool: &Pool<Sqlite>, md5: &str, suuid: &str, data: &str) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO trash (md5, suuid, data, delete_time) VALUES (?, ?, ?, ?)
+             ON CONFLICT(md5) DO UPDATE SET
+             suuid=excluded.suuid, data=excluded.data, delete_time=excluded.delete_time",
+    )
+    .bind(md5)
+    .bind(suuid)
+    .bind(data)
+    .bind(Utc::now().timestamp())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// insert the row which is from a backup, and keep its delete time
+pub async fn restore(pool: &Pool<Sqlite>, entry: &TrashEntry) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO trash (md5, suuid, data, delete_time) VALUES (?, ?, ?, ?)
+             ON CONFLICT(md5) DO NOTHING",
+    )
+    .bind(&entry.md5)
+    .bind(&entry.suuid)
+    .bind(&entry.data)
+    .bind(entry.delete_time)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// Put the rows back to their entry lists and remove them from the trash in one transaction
+pub async fn recover(pool: &Pool<Sqlite>, items: &[TrashEntry]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for item in items.iter() {
+        let uuid = serde_json::from_str::<RssEntry>(&item.data)?.uuid;
+        let table = entry::table_name(&item.suuid);
+
+        sqlx::query(&format!("DELETE FROM {table} WHERE uuid=?"))
+            .bind(&uuid)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(&format!("INSERT INTO {table} (uuid, data) VALUES (?, ?)"))
+            .bind(&uuid)
+            .bind(&item.data)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM trash WHERE md5=?")
+            .bind(&item.md5)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
     Ok(())
 }
 
-pub async fn insert(md5: &str) -> Result<()> {
-    sqlx::query("INSERT INTO trash (md5) VALUES (?)")
+pub async fn delete(pool: &Pool<Sqlite>, md5: &str) -> Result<()> {
+    sqlx::query("DELETE FROM trash WHERE md5=?")
         .bind(md5)
-        .execute(&pool())
+        .execute(pool)
         .await?;
     Ok(())
 }
 
-pub async fn delete_all() -> Result<()> {
-    sqlx::query("DELETE FROM trash").execute(&pool()).await?;
+pub async fn delete_all(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("DELETE FROM trash").execute(pool).await?;
+    Ok(())
+}
+
+// delete the rows which were deleted before the `timestamp`
+pub async fn delete_older_than(pool: &Pool<Sqlite>, timestamp: i64) -> Result<u64> {
+    let res = sqlx::query("DELETE FROM trash WHERE delete_time<?")
+        .bind(timestamp)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected())
+}
+
+// drop the recoverable data, but keep the md5 to avoid showing the entry again
+pub async fn forget(pool: &Pool<Sqlite>, md5: &str) -> Result<()> {
+    sqlx::query("UPDATE trash SET data='' WHERE md5=?")
+        .bind(md5)
+        .execute(pool)
+        .await?;
     Ok(())
 }
 
-pub async fn select(md5: &str) -> Result<String> {
+// drop the recoverable data of the rows which were deleted before the `timestamp`
+pub async fn forget_older_than(pool: &Pool<Sqlite>, timestamp: i64) -> Result<u64> {
+    let res = sqlx::query("UPDATE trash SET data='' WHERE delete_time<? AND data!=''")
+        .bind(timestamp)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected())
+}
+
+// only keep the latest `max_rows` rows
+pub async fn shrink(pool: &Pool<Sqlite>, max_rows: i64) -> Result<u64> {
+    let res = sqlx::query(
+        "DELETE FROM trash WHERE id NOT IN
+             (SELECT id FROM trash ORDER BY delete_time DESC, id DESC LIMIT ?)",
+    )
+    .bind(i64::max(0, max_rows))
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+pub async fn select(pool: &Pool<Sqlite>, md5: &str) -> Result<String> {
     let row = sqlx::query("SELECT * FROM trash WHERE md5=?")
         .bind(md5)
-        .fetch_one(&pool())
+        .fetch_one(pool)
         .await?;
 
     Ok(row.try_get("md5")?)
 }
 
-pub async fn is_exist(md5: &str) -> Result<()> {
-    select(md5).await?;
+pub async fn select_entry(pool: &Pool<Sqlite>, md5: &str) -> Result<TrashEntry> {
+    Ok(sqlx::query_as::<_, TrashEntry>(
+        "SELECT md5, suuid, data, delete_time FROM trash WHERE md5=?",
+    )
+    .bind(md5)
+    .fetch_one(pool)
+    .await?)
+}
+
+pub async fn select_recoverable(pool: &Pool<Sqlite>) -> Result<Vec<TrashEntry>> {
+    Ok(sqlx::query_as::<_, TrashEntry>(
+        "SELECT md5, suuid, data, delete_time FROM trash WHERE data!=''
+             ORDER BY delete_time DESC, id DESC",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn select_all(pool: &Pool<Sqlite>) -> Result<Vec<TrashEntry>> {
+    Ok(
+        sqlx::query_as::<_, TrashEntry>("SELECT md5, suuid, data, delete_time FROM trash")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn is_exist(pool: &Pool<Sqlite>, md5: &str) -> Result<()> {
+    select(pool, md5).await?;
     Ok(())
 }
 
-pub async fn row_count() -> Result<i64> {
+pub async fn row_count(pool: &Pool<Sqlite>) -> Result<i64> {
     let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM trash")
-        .fetch_one(&pool())
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count.0)
+}
+
+pub async fn row_count_older_than(pool: &Pool<Sqlite>, timestamp: i64) -> Result<i64> {
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM trash WHERE delete_time<?")
+        .bind(timestamp)
+        .fetch_one(pool)
         .await?;
 
     Ok(count.0)
 }
 
+// the bytes of the stored data, not including the sqlite page overhead
+pub async fn data_size(pool: &Pool<Sqlite>) -> Result<i64> {
+    let size: (i64,) = sqlx::query_as(
+        "SELECT COALESCE(SUM(LENGTH(md5) + LENGTH(suuid) + LENGTH(data) + 8), 0) FROM trash",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(size.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::db;
-    use std::sync::Mutex;
-
-    static MTX: Mutex<()> = Mutex::new(());
-    const DB_PATH: &str = "/tmp/rssbox-trash-test.db";
 
     #[tokio::test]
     async fn test_table_new() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await
+        let pool = db::memory_pool().await?;
+        new(&pool).await
+    }
+
+    #[tokio::test]
+    async fn test_migrate() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        sqlx::query("CREATE TABLE trash (id INTEGER PRIMARY KEY, md5 TEXT NOT NULL UNIQUE)")
+            .execute(&pool)
+            .await?;
+        sqlx::query("INSERT INTO trash (md5) VALUES ('md5-old')")
+            .execute(&pool)
+            .await?;
+
+        new(&pool).await?;
+        insert(&pool, "md5-1", "suuid-1", "data-1").await?;
+
+        let items = select_recoverable(&pool).await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].data, "data-1");
+
+        let item = select_entry(&pool, "md5-old").await?;
+        assert!(item.data.is_empty() && item.delete_time > 0);
+        assert!(data_size(&pool).await? > 0);
+        Ok(())
     }
 
     #[tokio::test]
     async fn test_delete_all() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await
     }
 
     #[tokio::test]
     async fn test_insert() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        insert("md5-1").await?;
-        insert("md5-2").await
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1", "suuid-1", "").await?;
+        insert(&pool, "md5-2", "suuid-1", "").await
     }
 
     #[tokio::test]
     async fn test_select_one() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        assert!(select("md5-1").await.is_err());
-
-        insert("md5-1").await?;
-        assert_eq!(select("md5-1").await?, "md5-1");
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        assert!(select(&pool, "md5-1").await.is_err());
+
+        insert(&pool, "md5-1", "suuid-1", "").await?;
+        assert_eq!(select(&pool, "md5-1").await?, "md5-1");
         Ok(())
     }
 
     #[tokio::test]
     async fn test_is_exist() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        insert("md5-1").await?;
-
-        assert!(is_exist("md5-0").await.is_err());
-        assert!(is_exist("md5-1").await.is_ok());
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1", "suuid-1", "").await?;
+
+        assert!(is_exist(&pool, "md5-0").await.is_err());
+        assert!(is_exist(&pool, "md5-1").await.is_ok());
         Ok(())
     }
 
     #[tokio::test]
     async fn test_row_count() -> Result<()> {
-        let _mtx = MTX.lock().unwrap();
-        db::init(DB_PATH).await;
-        new().await?;
-        delete_all().await?;
-        assert_eq!(row_count().await.unwrap(), 0);
-
-        insert("md5-1").await?;
-        assert_eq!(row_count().await.unwrap(), 1);
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        assert_eq!(row_count(&pool).await.unwrap(), 0);
+
+        insert(&pool, "md5-1", "suuid-1", "").await?;
+        assert_eq!(row_count(&pool).await.unwrap(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_older_than() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1", "suuid-1", "").await?;
+        insert(&pool, "md5-2", "suuid-1", "").await?;
+
+        let now = Utc::now().timestamp();
+        assert_eq!(row_count_older_than(&pool, now - 60).await?, 0);
+        assert_eq!(delete_older_than(&pool, now - 60).await?, 0);
+        assert_eq!(row_count_older_than(&pool, now + 60).await?, 2);
+        assert_eq!(delete_older_than(&pool, now + 60).await?, 2);
+        assert_eq!(row_count(&pool).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shrink() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1", "suuid-1", "").await?;
+        insert(&pool, "md5-2", "suuid-1", "").await?;
+        insert(&pool, "md5-3", "suuid-1", "").await?;
+
+        assert_eq!(shrink(&pool, 2).await?, 1);
+        assert!(is_exist(&pool, "md5-1").await.is_err());
+        assert!(is_exist(&pool, "md5-3").await.is_ok());
+        assert_eq!(data_size(&pool).await?, 2 * (5 + 7 + 8));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recoverable() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        delete_all(&pool).await?;
+        insert(&pool, "md5-1", "suuid-1", "data-1").await?;
+        insert(&pool, "md5-2", "suuid-1", "data-2").await?;
+        insert(&pool, "md5-3", "suuid-1", "").await?;
+
+        let items = select_recoverable(&pool).await?;
+        assert_eq!(items.len(), 2);
+        assert_eq!(select_entry(&pool, "md5-1").await?.data, "data-1");
+
+        forget(&pool, "md5-1").await?;
+        assert!(is_exist(&pool, "md5-1").await.is_ok());
+        assert_eq!(select_recoverable(&pool).await?.len(), 1);
+
+        let now = Utc::now().timestamp();
+        assert_eq!(forget_older_than(&pool, now + 60).await?, 1);
+        assert!(select_recoverable(&pool).await?.is_empty());
+
+        delete(&pool, "md5-2").await?;
+        assert!(is_exist(&pool, "md5-2").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+
+        let entry = TrashEntry {
+            md5: "md5-1".to_string(),
+            suuid: "suuid-1".to_string(),
+            data: "data-1".to_string(),
+            delete_time: 100,
+        };
+        restore(&pool, &entry).await?;
+        restore(&pool, &entry).await?;
+
+        let items = select_all(&pool).await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].delete_time, 100);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recover() -> Result<()> {
+        let pool = db::memory_pool().await?;
+        new(&pool).await?;
+        entry::new(&pool, "suuid-1").await?;
+
+        let data = serde_json::to_string(&RssEntry {
+            uuid: "uuid-1".to_string(),
+            url: "https://blog.rust-lang.org/1".to_string(),
+            ..Default::default()
+        })?;
+        insert(&pool, "md5-1", "suuid-1", &data).await?;
+
+        let items = select_recoverable(&pool).await?;
+        recover(&pool, &items).await?;
+        recover(&pool, &items).await?;
+
+        assert!(is_exist(&pool, "md5-1").await.is_err());
+        assert_eq!(entry::select_all(&pool, "suuid-1").await?.len(), 1);
         Ok(())
     }
 }
@@ -3,5 +3,11 @@ extern crate rssbox;
 #[cfg(not(target_os = "android"))]
 #[tokio::main]
 async fn main() {
+    // Print the description of the local api for the tools, such as the code generators
+    if std::env::args().any(|arg| arg == "--openapi") {
+        print!("{}", rssbox::logic::localapi::OPENAPI);
+        return;
+    }
+
     rssbox::desktop_main().await;
 }
@@ -0,0 +1,254 @@
+use super::{Engine, Event};
+use crate::db::{entry::RssEntry, rss::RssConfig};
+use anyhow::{bail, Result};
+
+// The entry list of the favorite entrys, it doesn't belong to any feed
+pub const FAVORITE_UUID: &str = "favorite-uuid";
+
+impl Engine {
+    // The oldest entry is the first
+    pub async fn entrys(&self, suuid: &str) -> Result<Vec<RssEntry>> {
+        Ok(self
+            .repo
+            .select_all_entrys(suuid)
+            .await?
+            .into_iter()
+            .filter_map(|item| serde_json::from_str::<RssEntry>(&item.data).ok())
+            .collect())
+    }
+
+    // The entry is found by the uuid or the prefix of the uuid in all the feeds
+    pub async fn find_entry(&self, id: &str) -> Result<(RssConfig, RssEntry)> {
+        let id = id.trim();
+        if id.is_empty() {
+            bail!("empty entry id");
+        }
+
+        let mut items = vec![];
+        for rss in self.feeds().await?.into_iter() {
+            for entry in self.entrys(&rss.uuid).await?.into_iter() {
+                if entry.uuid.starts_with(id) {
+                    items.push((rss.clone(), entry));
+                }
+            }
+        }
+
+        match items.len() {
+            0 => bail!("no such entry: {id}"),
+            1 => Ok(items.remove(0)),
+            _ => bail!("ambiguous entry id: {id}"),
+        }
+    }
+
+    // The read state is mirrored to the server if the feed belongs to the account
+    pub async fn set_read(&self, suuid: &str, uuid: &str, is_read: bool) -> Result<RssEntry> {
+        let item = self.repo.select_entry(suuid, uuid).await?;
+        let mut entry = serde_json::from_str::<RssEntry>(&item.data)?;
+
+        if entry.is_read == is_read {
+            return Ok(entry);
+        }
+
+        entry.is_read = is_read;
+        self.repo
+            .update_entry(suuid, uuid, &serde_json::to_string(&entry)?)
+            .await?;
+
+        if !self.feed(suuid).await?.remote_id.is_empty() {
+            if let Err(e) = self.remote.set_read(vec![uuid.to_string()], is_read).await {
+                log::warn!("{e:?}");
+            }
+        }
+
+        self.emit(Event::EntryRead {
+            suuid: suuid.to_string(),
+            uuid: uuid.to_string(),
+            is_read,
+        });
+        Ok(entry)
+    }
+
+    // The newest favorite entry is the first
+    pub async fn favorites(&self) -> Result<Vec<RssEntry>> {
+        let mut items = self.entrys(FAVORITE_UUID).await?;
+        items.reverse();
+        Ok(items)
+    }
+
+    pub async fn add_favorite(&self, entry: RssEntry) -> Result<()> {
+        let data = serde_json::to_string(&entry)?;
+        self.repo
+            .insert_entry(FAVORITE_UUID, &entry.uuid, &data)
+            .await?;
+
+        self.emit(Event::FavoriteAdded(entry));
+        Ok(())
+    }
+
+    // Save the new entrys of the feed and return them. The entrys which have been saved are skipped by the url.
+    // The read states of the saved entrys come from the server if the feed belongs to the account.
+    pub async fn sync(&self, suuid: &str) -> Result<Vec<RssEntry>> {
+        let rss = self.feed(suuid).await?;
+        let is_remote = !rss.remote_id.is_empty();
+
+        let entrys = self.remote.fetch_entrys(&rss).await?;
+        let old_entrys = self.entrys(suuid).await?;
+
+        let mut new_entrys: Vec<RssEntry> = vec![];
+        for mut entry in entrys.into_iter() {
+            if let Some(old_entry) = old_entrys.iter().find(|item| item.url == entry.url) {
+                if is_remote && old_entry.is_read != entry.is_read {
+                    let mut old_entry = old_entry.clone();
+                    old_entry.is_read = entry.is_read;
+                    self.repo
+                        .update_entry(suuid, &old_entry.uuid, &serde_json::to_string(&old_entry)?)
+                        .await?;
+
+                    self.emit(Event::EntryRead {
+                        suuid: suuid.to_string(),
+                        uuid: old_entry.uuid,
+                        is_read: entry.is_read,
+                    });
+                }
+                continue;
+            }
+
+            if new_entrys.iter().any(|item| item.url == entry.url) {
+                continue;
+            }
+
+            entry.suuid = suuid.to_string();
+            self.repo
+                .insert_entry(suuid, &entry.uuid, &serde_json::to_string(&entry)?)
+                .await?;
+            new_entrys.push(entry);
+        }
+
+        if let Err(e) = self.remote.saved(&rss).await {
+            log::warn!("{e:?}");
+        }
+
+        self.emit(Event::Synced {
+            suuid: suuid.to_string(),
+            entrys: new_entrys.clone(),
+        });
+        Ok(new_entrys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{memory_engine, rss_config, rss_entry};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync() -> Result<()> {
+        let (engine, remote) = memory_engine().await?;
+        let rss = engine
+            .add_feed(rss_config("Rust", "https://blog.rust-lang.org/feed.xml"))
+            .await?;
+
+        *remote.entrys.lock().unwrap() = vec![
+            rss_entry("uuid-1", "https://blog.rust-lang.org/1"),
+            rss_entry("uuid-2", "https://blog.rust-lang.org/2"),
+            rss_entry("uuid-2-1", "https://blog.rust-lang.org/2"),
+        ];
+
+        let mut events = engine.subscribe();
+        let entrys = engine.sync(&rss.uuid).await?;
+        assert_eq!(entrys.len(), 2);
+        assert_eq!(entrys[0].suuid, rss.uuid);
+        assert!(matches!(
+            events.try_recv()?,
+            Event::Synced { suuid, entrys } if suuid == rss.uuid && entrys.len() == 2
+        ));
+
+        remote
+            .entrys
+            .lock()
+            .unwrap()
+            .push(rss_entry("uuid-3", "https://blog.rust-lang.org/3"));
+        assert_eq!(engine.sync(&rss.uuid).await?.len(), 1);
+        assert_eq!(engine.entrys(&rss.uuid).await?.len(), 3);
+
+        assert!(matches!(events.try_recv()?, Event::Synced { .. }));
+        assert!(engine.sync("unknown").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_remote_read_state() -> Result<()> {
+        let (engine, remote) = memory_engine().await?;
+        let mut rss = engine
+            .add_feed(rss_config("Rust", "https://blog.rust-lang.org/feed.xml"))
+            .await?;
+        rss.remote_id = "remote-1".to_string();
+        engine.update_feed(rss.clone()).await?;
+
+        *remote.entrys.lock().unwrap() = vec![rss_entry("uuid-1", "https://blog.rust-lang.org/1")];
+        engine.sync(&rss.uuid).await?;
+
+        remote.entrys.lock().unwrap()[0].is_read = true;
+        let mut events = engine.subscribe();
+        assert!(engine.sync(&rss.uuid).await?.is_empty());
+
+        assert!(engine.entrys(&rss.uuid).await?[0].is_read);
+        assert!(matches!(
+            events.try_recv()?,
+            Event::EntryRead { uuid, is_read: true, .. } if uuid == "uuid-1"
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_read() -> Result<()> {
+        let (engine, remote) = memory_engine().await?;
+        let rss = engine
+            .add_feed(rss_config("Rust", "https://blog.rust-lang.org/feed.xml"))
+            .await?;
+
+        *remote.entrys.lock().unwrap() = vec![rss_entry("uuid-1", "https://blog.rust-lang.org/1")];
+        engine.sync(&rss.uuid).await?;
+
+        let (found_rss, entry) = engine.find_entry("uuid").await?;
+        assert_eq!(
+            (found_rss.uuid, entry.uuid),
+            (rss.uuid.clone(), "uuid-1".to_string())
+        );
+
+        let mut events = engine.subscribe();
+        assert!(engine.set_read(&rss.uuid, "uuid-1", true).await?.is_read);
+        assert!(engine.entrys(&rss.uuid).await?[0].is_read);
+        assert!(matches!(
+            events.try_recv()?,
+            Event::EntryRead { is_read: true, .. }
+        ));
+
+        // nothing changes, so nothing is sent
+        engine.set_read(&rss.uuid, "uuid-1", true).await?;
+        assert!(events.try_recv().is_err());
+
+        // the feed doesn't belong to the account
+        assert!(remote.read_uuids.lock().unwrap().is_empty());
+        assert!(engine.find_entry("unknown").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_favorites() -> Result<()> {
+        let (engine, _) = memory_engine().await?;
+        engine.repo.new_entry_list(FAVORITE_UUID).await?;
+
+        engine
+            .add_favorite(rss_entry("uuid-1", "https://blog.rust-lang.org/1"))
+            .await?;
+        engine
+            .add_favorite(rss_entry("uuid-2", "https://blog.rust-lang.org/2"))
+            .await?;
+
+        let items = engine.favorites().await?;
+        assert_eq!(items[0].uuid, "uuid-2");
+        assert_eq!(items.len(), 2);
+        Ok(())
+    }
+}
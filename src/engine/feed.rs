@@ -0,0 +1,234 @@
+use super::{Engine, Event};
+use crate::db::{rss::RssConfig, ComEntry};
+use crate::util;
+use anyhow::{bail, Result};
+use uuid::Uuid;
+
+#[derive(Debug, Default)]
+pub struct AddedFeeds {
+    pub feeds: Vec<RssConfig>,
+
+    // the urls of the feeds which fail to be subscribed in the server, and the reasons
+    pub failures: Vec<(String, String)>,
+}
+
+impl Engine {
+    pub async fn feeds(&self) -> Result<Vec<RssConfig>> {
+        Ok(self
+            .repo
+            .select_all_rss()
+            .await?
+            .into_iter()
+            .filter_map(|item| serde_json::from_str::<RssConfig>(&item.data).ok())
+            .collect())
+    }
+
+    pub async fn feed(&self, uuid: &str) -> Result<RssConfig> {
+        let item = self.repo.select_rss(uuid).await?;
+        Ok(serde_json::from_str::<RssConfig>(&item.data)?)
+    }
+
+    // The feed is found by the uuid, the prefix of the uuid or the url
+    pub async fn find_feed(&self, id: &str) -> Result<RssConfig> {
+        let id = id.trim();
+        if id.is_empty() {
+            bail!("empty feed id");
+        }
+
+        let mut items = self
+            .feeds()
+            .await?
+            .into_iter()
+            .filter(|item| item.uuid.starts_with(id) || util::url::is_same(&item.url, id))
+            .collect::<Vec<_>>();
+
+        match items.len() {
+            0 => bail!("no such feed: {id}"),
+            1 => Ok(items.remove(0)),
+            _ => bail!("ambiguous feed id: {id}"),
+        }
+    }
+
+    pub async fn add_feed(&self, mut rss: RssConfig) -> Result<RssConfig> {
+        if self
+            .feeds()
+            .await?
+            .iter()
+            .any(|item| util::url::is_same(&item.url, &rss.url))
+        {
+            bail!("the feed is already subscribed: {}", rss.url);
+        }
+
+        rss.uuid = Uuid::new_v4().to_string();
+        rss.remote_id = self.remote.subscribe(&rss).await?;
+
+        let config = serde_json::to_string(&rss)?;
+        self.repo.insert_rss(&rss.uuid, &config).await?;
+        self.repo.new_entry_list(&rss.uuid).await?;
+
+        self.emit(Event::FeedAdded(rss.clone()));
+        Ok(rss)
+    }
+
+    // Subscribe the feeds in one database transaction. The feeds which have been subscribed are skipped.
+    // The feeds which fail to be subscribed in the server don't stop the others.
+    pub async fn add_feeds(&self, items: Vec<RssConfig>) -> Result<AddedFeeds> {
+        let mut urls = self
+            .feeds()
+            .await?
+            .into_iter()
+            .map(|item| item.url)
+            .collect::<Vec<_>>();
+
+        let (mut rss_list, mut entrys, mut failures) = (vec![], vec![], vec![]);
+        for mut rss in items.into_iter() {
+            if urls.iter().any(|url| util::url::is_same(url, &rss.url)) {
+                continue;
+            }
+            urls.push(rss.url.clone());
+
+            rss.uuid = Uuid::new_v4().to_string();
+            rss.remote_id = match self.remote.subscribe(&rss).await {
+                Ok(id) => id,
+                Err(e) => {
+                    failures.push((rss.url, format!("{e:?}")));
+                    continue;
+                }
+            };
+
+            entrys.push(ComEntry {
+                uuid: rss.uuid.clone(),
+                data: serde_json::to_string(&rss)?,
+            });
+            rss_list.push(rss);
+        }
+
+        if !rss_list.is_empty() {
+            // don't leave the subscriptions in the server which are unknown to the device
+            if let Err(e) = self.repo.insert_all_rss(&entrys).await {
+                for rss in rss_list.iter() {
+                    if let Err(e) = self.remote.unsubscribe(rss).await {
+                        log::warn!("{e:?}");
+                    }
+                }
+                return Err(e);
+            }
+        }
+
+        for rss in rss_list.iter() {
+            self.emit(Event::FeedAdded(rss.clone()));
+        }
+
+        Ok(AddedFeeds {
+            feeds: rss_list,
+            failures,
+        })
+    }
+
+    pub async fn update_feed(&self, rss: RssConfig) -> Result<()> {
+        let config = serde_json::to_string(&rss)?;
+        self.repo.update_rss(&rss.uuid, &config).await?;
+
+        self.emit(Event::FeedUpdated(rss));
+        Ok(())
+    }
+
+    // The feed is removed even if it fails to unsubscribe it from the server
+    pub async fn remove_feed(&self, uuid: &str) -> Result<RssConfig> {
+        let rss = self.feed(uuid).await?;
+
+        if let Err(e) = self.remote.unsubscribe(&rss).await {
+            log::warn!("{e:?}");
+        }
+
+        self.repo.delete_rss(uuid).await?;
+        self.repo.drop_entry_list(uuid).await?;
+
+        self.emit(Event::FeedRemoved(uuid.to_string()));
+        Ok(rss)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{memory_engine, rss_config};
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_feed() -> Result<()> {
+        let (engine, _) = memory_engine().await?;
+        let mut events = engine.subscribe();
+
+        let rss = engine
+            .add_feed(rss_config("Rust", "https://blog.rust-lang.org/feed.xml"))
+            .await?;
+        assert!(!rss.uuid.is_empty());
+        assert!(matches!(events.try_recv()?, Event::FeedAdded(item) if item.uuid == rss.uuid));
+
+        assert!(engine
+            .add_feed(rss_config("Rust", "https://BLOG.rust-lang.org/feed.xml/"))
+            .await
+            .is_err());
+
+        assert_eq!(engine.find_feed(&rss.uuid[..8]).await?.name, "Rust");
+        assert_eq!(
+            engine
+                .find_feed("https://blog.rust-lang.org/feed.xml")
+                .await?
+                .uuid,
+            rss.uuid
+        );
+        assert!(engine.find_feed("unknown").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_add_feeds() -> Result<()> {
+        let (engine, remote) = memory_engine().await?;
+        engine
+            .add_feed(rss_config("Go", "https://go.dev/blog/feed.atom"))
+            .await?;
+
+        remote
+            .fail_urls
+            .lock()
+            .unwrap()
+            .push("https://example.com/feed.xml".to_string());
+
+        let items = engine
+            .add_feeds(vec![
+                rss_config("Go", "https://go.dev/blog/feed.atom"),
+                rss_config("Rust", "https://blog.rust-lang.org/feed.xml"),
+                rss_config("Rust", "https://blog.rust-lang.org/feed.xml"),
+                rss_config("Example", "https://example.com/feed.xml"),
+            ])
+            .await?;
+        assert_eq!(items.feeds.len(), 1);
+        assert_eq!(items.failures.len(), 1);
+        assert_eq!(items.failures[0].0, "https://example.com/feed.xml");
+        assert_eq!(engine.feeds().await?.len(), 2);
+        assert!(engine.add_feeds(vec![]).await?.feeds.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_and_remove_feed() -> Result<()> {
+        let (engine, _) = memory_engine().await?;
+        let mut rss = engine
+            .add_feed(rss_config("Rust", "https://blog.rust-lang.org/feed.xml"))
+            .await?;
+
+        let mut events = engine.subscribe();
+
+        rss.name = "Rust Blog".to_string();
+        engine.update_feed(rss.clone()).await?;
+        assert_eq!(engine.feed(&rss.uuid).await?.name, "Rust Blog");
+        assert!(matches!(events.try_recv()?, Event::FeedUpdated(item) if item.name == "Rust Blog"));
+
+        engine.remove_feed(&rss.uuid).await?;
+        assert!(engine.feeds().await?.is_empty());
+        assert!(matches!(events.try_recv()?, Event::FeedRemoved(uuid) if uuid == rss.uuid));
+        assert!(engine.remove_feed(&rss.uuid).await.is_err());
+        Ok(())
+    }
+}
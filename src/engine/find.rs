@@ -0,0 +1,51 @@
+use super::{Engine, Event};
+use crate::util::crypto::md5_hex;
+use anyhow::Result;
+
+impl Engine {
+    // The feeds in the blacklist are hidden in the find list
+    pub async fn is_blacklist(&self, url: &str) -> bool {
+        self.repo.is_exist_blacklist(&md5_hex(url)).await.is_ok()
+    }
+
+    pub async fn set_blacklist(&self, url: &str, is_blacklist: bool) -> Result<()> {
+        if is_blacklist {
+            self.repo.insert_blacklist(&md5_hex(url)).await?;
+        } else {
+            self.repo.delete_blacklist(&md5_hex(url)).await?;
+        }
+
+        self.emit(Event::Blacklist {
+            url: url.to_string(),
+            is_blacklist,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::memory_engine;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blacklist() -> Result<()> {
+        let (engine, _) = memory_engine().await?;
+        let url = "https://blog.rust-lang.org/feed.xml";
+
+        let mut events = engine.subscribe();
+        engine.set_blacklist(url, true).await?;
+        assert!(engine.is_blacklist(url).await);
+        assert!(matches!(
+            events.try_recv()?,
+            Event::Blacklist {
+                is_blacklist: true,
+                ..
+            }
+        ));
+
+        engine.set_blacklist(url, false).await?;
+        assert!(!engine.is_blacklist(url).await);
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use crate::config::Config;
+use crate::db::{entry::RssEntry, rss::RssConfig, Repository};
+use anyhow::Result;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+mod entry;
+mod feed;
+mod find;
+mod setting;
+
+pub use entry::FAVORITE_UUID;
+pub use feed::AddedFeeds;
+
+const EVENT_CAPACITY: usize = 1024;
+
+// The changes which have been saved. The frontends apply them to their own views.
+#[derive(Debug, Clone)]
+pub enum Event {
+    FeedAdded(RssConfig),
+    FeedUpdated(RssConfig),
+    FeedRemoved(String),
+
+    // the new entrys of the feed, and the oldest one is the first.
+    // It's sent after every successful sync, even if there are no new entrys.
+    Synced {
+        suuid: String,
+        entrys: Vec<RssEntry>,
+    },
+
+    EntryRead {
+        suuid: String,
+        uuid: String,
+        is_read: bool,
+    },
+
+    FavoriteAdded(RssEntry),
+
+    Blacklist {
+        url: String,
+        is_blacklist: bool,
+    },
+
+    SettingsChanged(Box<Config>),
+}
+
+// The network side of the feeds. The feeds with a `remote_id` belong to the server of the account.
+#[async_trait]
+pub trait Remote: Send + Sync {
+    // the entrys which are not in the trash, and the oldest one is the first
+    async fn fetch_entrys(&self, rss: &RssConfig) -> Result<Vec<RssEntry>>;
+
+    // the fetched entrys have been saved
+    async fn saved(&self, _rss: &RssConfig) -> Result<()> {
+        Ok(())
+    }
+
+    // return the feed id in the server, empty means the feed is fetched directly
+    async fn subscribe(&self, rss: &RssConfig) -> Result<String>;
+
+    async fn unsubscribe(&self, rss: &RssConfig) -> Result<()>;
+    async fn set_read(&self, uuids: Vec<String>, is_read: bool) -> Result<()>;
+}
+
+// The subscriptions, entrys, sync, find and settings without any UI.
+// The database is the state, and every change is sent to the subscribers as an `Event`.
+pub struct Engine {
+    repo: Arc<dyn Repository>,
+    remote: Arc<dyn Remote>,
+    events: broadcast::Sender<Event>,
+}
+
+static ENGINE: Lazy<Mutex<Option<Arc<Engine>>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn get() -> Arc<Engine> {
+    ENGINE
+        .lock()
+        .unwrap()
+        .clone()
+        .expect("engine is not initialized")
+}
+
+pub fn set(engine: Engine) {
+    *ENGINE.lock().unwrap() = Some(Arc::new(engine));
+}
+
+impl Engine {
+    pub fn new(repo: Arc<dyn Repository>, remote: Arc<dyn Remote>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            repo,
+            remote,
+            events,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    // It fails only if nobody subscribes, such as the command line client
+    fn emit(&self, event: Event) {
+        _ = self.events.send(event);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::db::SqliteRepository;
+
+    #[derive(Default)]
+    pub struct FakeRemote {
+        pub entrys: Mutex<Vec<RssEntry>>,
+        pub read_uuids: Mutex<Vec<String>>,
+        pub fail_urls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Remote for FakeRemote {
+        async fn fetch_entrys(&self, _rss: &RssConfig) -> Result<Vec<RssEntry>> {
+            Ok(self.entrys.lock().unwrap().clone())
+        }
+
+        async fn subscribe(&self, rss: &RssConfig) -> Result<String> {
+            if self.fail_urls.lock().unwrap().contains(&rss.url) {
+                anyhow::bail!("failed to subscribe: {}", rss.url);
+            }
+            Ok(String::default())
+        }
+
+        async fn unsubscribe(&self, _rss: &RssConfig) -> Result<()> {
+            Ok(())
+        }
+
+        async fn set_read(&self, uuids: Vec<String>, _is_read: bool) -> Result<()> {
+            self.read_uuids.lock().unwrap().extend(uuids);
+            Ok(())
+        }
+    }
+
+    pub async fn memory_engine() -> Result<(Engine, Arc<FakeRemote>)> {
+        let remote = Arc::new(FakeRemote::default());
+        let engine = Engine::new(Arc::new(SqliteRepository::memory().await?), remote.clone());
+        Ok((engine, remote))
+    }
+
+    pub fn rss_config(name: &str, url: &str) -> RssConfig {
+        RssConfig {
+            uuid: String::default(),
+            name: name.to_string(),
+            url: url.to_string(),
+            icon_index: 0,
+            use_http_proxy: false,
+            use_socks5_proxy: false,
+            is_favorite: false,
+            feed_format: "AUTO".to_string(),
+            category: String::default(),
+            remote_id: String::default(),
+        }
+    }
+
+    pub fn rss_entry(uuid: &str, url: &str) -> RssEntry {
+        RssEntry {
+            uuid: uuid.to_string(),
+            url: url.to_string(),
+            title: uuid.to_string(),
+            ..Default::default()
+        }
+    }
+}
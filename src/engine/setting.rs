@@ -0,0 +1,18 @@
+use super::{Engine, Event};
+use crate::config::{self, Config};
+use anyhow::Result;
+
+impl Engine {
+    pub fn settings(&self) -> Config {
+        config::all()
+    }
+
+    pub fn update_settings(&self, update: impl FnOnce(&mut Config)) -> Result<()> {
+        let mut all = config::all();
+        update(&mut all);
+        config::save(all.clone())?;
+
+        self.emit(Event::SettingsChanged(Box::new(all)));
+        Ok(())
+    }
+}
@@ -9,14 +9,22 @@ use std::{
     time::Duration,
 };
 
+mod account;
 mod config;
 pub mod db;
+pub mod engine;
 pub mod logic;
 pub mod util;
 mod version;
 
 static SYNC_TIMESTAMP_CACHE: AtomicI64 = AtomicI64::new(0);
 
+// the time of the last auto backup attempt
+static BACKUP_TIMESTAMP_CACHE: AtomicI64 = AtomicI64::new(0);
+
+// retry the failed auto backup after an hour
+const AUTO_BACKUP_RETRY_INTERVAL: i64 = 60 * 60;
+
 #[cfg(not(target_os = "android"))]
 pub fn init_logger() {
     use chrono::Local;
@@ -69,6 +77,7 @@ async fn ui_before() {
     init_logger();
     config::init();
     db::init(config::db_path().to_str().expect("invalid db path")).await;
+    logic::init_engine();
 }
 
 fn ui_after(ui: &AppWindow) {
@@ -99,6 +108,29 @@ fn sync_rss_timer(ui: &AppWindow) -> Timer {
     timer
 }
 
+fn auto_backup_timer(ui: &AppWindow) -> Timer {
+    let ui_handle = ui.as_weak();
+
+    let timer = Timer::default();
+    timer.start(TimerMode::Repeated, Duration::from_secs(60), move || {
+        let config = config::backup_recover();
+        let Some(interval) = config.auto_backup_interval() else {
+            return;
+        };
+
+        let now = Utc::now().timestamp();
+        if config.last_auto_backup_time + interval > now
+            || BACKUP_TIMESTAMP_CACHE.load(Ordering::SeqCst) + AUTO_BACKUP_RETRY_INTERVAL > now
+        {
+            return;
+        }
+
+        BACKUP_TIMESTAMP_CACHE.store(now, Ordering::SeqCst);
+        ui_handle.unwrap().global::<Logic>().invoke_auto_backup();
+    });
+    timer
+}
+
 #[cfg(target_os = "android")]
 #[no_mangle]
 #[tokio::main]
@@ -110,6 +142,7 @@ async fn android_main(app: slint::android::AndroidApp) {
     let ui = AppWindow::new().unwrap();
     ui_after(&ui);
     let _timer = sync_rss_timer(&ui);
+    let _backup_timer = auto_backup_timer(&ui);
     ui.run().unwrap();
 
     log::debug!("exit...");
@@ -123,7 +156,11 @@ pub async fn desktop_main() {
     let ui = AppWindow::new().unwrap();
     ui_after(&ui);
 
+    #[cfg(feature = "desktop")]
+    logic::localapi::init();
+
     let _timer = sync_rss_timer(&ui);
+    let _backup_timer = auto_backup_timer(&ui);
     ui.run().unwrap();
 
     log::debug!("exit...");
@@ -0,0 +1,527 @@
+use super::{
+    entry::FAVORITE_UUID,
+    message::{async_message_success, async_message_warn},
+};
+use crate::slint_generatedAppWindow::{
+    AppWindow, Logic, RssConfig as UIRssConfig, RssEntry as UIRssEntry, SettingAccount, Store,
+};
+use crate::{
+    account::{self, RemoteEntry, RemoteFeed, Service},
+    config,
+    db::{self, entry::RssEntry, rss::RssConfig},
+    message_info, store_favorite_entrys, store_rss_lists,
+    util::{translator::tr, url},
+};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use slint::{ComponentHandle, Model, VecModel, Weak};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+// The names which are shown in the setting page
+const KINDS: [(&str, &str); 4] = [
+    (account::KIND_DIRECT, "Direct"),
+    (account::KIND_GREADER, "Google Reader"),
+    (account::KIND_FEVER, "Fever"),
+    (account::KIND_MINIFLUX, "Miniflux"),
+];
+
+// The logged-in service is reused until the account setting changes
+static SERVICE: Lazy<Mutex<Option<Arc<dyn Service>>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn init(ui: &AppWindow) {
+    ui.global::<Logic>().on_get_setting_account(move || {
+        let config = config::account();
+
+        SettingAccount {
+            kind: kind_name(&config.kind).into(),
+            url: config.url.into(),
+            username: config.username.into(),
+            password: config.password.into(),
+            use_http_proxy: config.use_http_proxy,
+            use_socks5_proxy: config.use_socks5_proxy,
+        }
+    });
+
+    ui.global::<Logic>().on_set_setting_account(move |setting| {
+        let mut all = config::all();
+        let (kind, url) = (kind_from_name(&setting.kind), setting.url.trim());
+
+        // the pulled items belong to the old server
+        if all.account.kind != kind || all.account.url != url {
+            all.account.since_id = 0;
+        }
+
+        all.account.kind = kind.into();
+        all.account.url = url.into();
+        all.account.username = setting.username.trim().into();
+        all.account.password = setting.password.into();
+        all.account.use_http_proxy = setting.use_http_proxy;
+        all.account.use_socks5_proxy = setting.use_socks5_proxy;
+        _ = config::save(all);
+
+        reset();
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_login_account(move || {
+        let ui = ui_handle.unwrap();
+
+        if !is_enabled() {
+            message_info!(ui, tr("未设置账户"));
+            return;
+        }
+
+        message_info!(ui, tr("正在登录..."));
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            reset();
+            match service().await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("登录失败"), tr("原因")),
+                ),
+                _ => async_message_success(ui.clone(), tr("登录成功")),
+            }
+        });
+    });
+}
+
+fn kind_name(kind: &str) -> &'static str {
+    KINDS
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, name)| *name)
+        .unwrap_or(KINDS[0].1)
+}
+
+fn kind_from_name(name: &str) -> &'static str {
+    KINDS
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(kind, _)| *kind)
+        .unwrap_or(account::KIND_DIRECT)
+}
+
+pub fn is_enabled() -> bool {
+    account::is_enabled(&config::account())
+}
+
+pub fn reset() {
+    *SERVICE.lock().unwrap() = None;
+}
+
+async fn service() -> Result<Arc<dyn Service>> {
+    if let Some(service) = SERVICE.lock().unwrap().clone() {
+        return Ok(service);
+    }
+
+    let service: Arc<dyn Service> = Arc::from(account::connect(&config::account()).await?);
+    *SERVICE.lock().unwrap() = Some(service.clone());
+    Ok(service)
+}
+
+fn is_remote_rss(ui: &AppWindow, suuid: &str) -> bool {
+    super::rss::get_rss_config(ui, suuid).is_some_and(|rss| !rss.remote_id.is_empty())
+}
+
+// Mirror the starred state of the entrys which belong to a remote feed
+pub fn mirror_starred(ui: &AppWindow, suuid: &str, uuids: Vec<String>, is_starred: bool) {
+    if uuids.is_empty() || !is_remote_rss(ui, suuid) {
+        return;
+    }
+
+    tokio::spawn(async move {
+        if let Err(e) = _set_starred(uuids, is_starred).await {
+            log::warn!("{e:?}");
+        }
+    });
+}
+
+pub(super) async fn _set_read(uuids: Vec<String>, is_read: bool) -> Result<()> {
+    service().await?.set_read(&uuids, is_read).await
+}
+
+async fn _set_starred(uuids: Vec<String>, is_starred: bool) -> Result<()> {
+    service().await?.set_starred(&uuids, is_starred).await
+}
+
+// Return the feed id in the server. The feeds are only subscribed in the server when the account is enabled.
+pub async fn subscribe(rss: &RssConfig) -> Result<String> {
+    if !is_enabled() {
+        return Ok(String::default());
+    }
+
+    service().await?.subscribe(&rss.url, &rss.name).await
+}
+
+pub async fn unsubscribe(remote_id: &str) -> Result<()> {
+    if remote_id.is_empty() || !is_enabled() {
+        return Ok(());
+    }
+
+    service().await?.unsubscribe(remote_id).await
+}
+
+fn to_rss_entry(suuid: &str, entry: RemoteEntry) -> RssEntry {
+    RssEntry {
+        suuid: suuid.to_string(),
+        uuid: entry.id,
+        url: entry.url,
+        title: entry.title,
+        pub_date: entry.pub_date,
+        tags: entry.tags,
+        author: entry.author,
+        summary: super::rss::parse_summary(&entry.summary, false),
+        is_read: entry.is_read,
+    }
+}
+
+// The entry uuid is the entry id in the server, so that the states can be mirrored
+pub async fn fetch_entrys(suuid: &str, remote_id: &str) -> Result<Vec<RssEntry>> {
+    let entrys = service()
+        .await?
+        .entries(remote_id)
+        .await?
+        .into_iter()
+        .filter(|item| !item.url.is_empty() && !item.title.is_empty())
+        .map(|item| to_rss_entry(suuid, item))
+        .collect();
+
+    let unique_entrys = super::rss::skip_trash_entrys(db::repo().as_ref(), entrys).await;
+    Ok(unique_entrys.into_iter().rev().collect())
+}
+
+// The entrys of the remote feed have been saved, so the server won't return them again
+pub async fn saved(remote_id: &str) -> Result<()> {
+    if remote_id.is_empty() || !is_enabled() {
+        return Ok(());
+    }
+
+    service().await?.saved(remote_id).await
+}
+
+// the added, the updated and the removed feeds
+type MergedFeeds = (Vec<RssConfig>, Vec<RssConfig>, Vec<RssConfig>);
+
+// Match the remote feeds with the local ones by the remote id, then by the url.
+// The local feeds which have been unsubscribed in the server are removed.
+fn merge_feeds(local: &[RssConfig], remote: Vec<RemoteFeed>) -> MergedFeeds {
+    let removed = local
+        .iter()
+        .filter(|item| {
+            !item.remote_id.is_empty() && !remote.iter().any(|feed| feed.id == item.remote_id)
+        })
+        .cloned()
+        .collect();
+
+    let (mut added, mut updated) = (vec![], vec![]);
+
+    for feed in remote.into_iter() {
+        if local.iter().any(|item| item.remote_id == feed.id) {
+            continue;
+        }
+
+        match local
+            .iter()
+            .find(|item| item.remote_id.is_empty() && url::is_same(&item.url, &feed.url))
+        {
+            Some(item) => {
+                let mut item = item.clone();
+                item.remote_id = feed.id;
+                updated.push(item);
+            }
+            _ => added.push(RssConfig {
+                uuid: Uuid::new_v4().to_string(),
+                name: if feed.title.is_empty() {
+                    feed.url.clone()
+                } else {
+                    feed.title
+                },
+                url: feed.url,
+                icon_index: 0,
+                use_http_proxy: false,
+                use_socks5_proxy: false,
+                is_favorite: false,
+                feed_format: "AUTO".to_string(),
+                category: feed.category,
+                remote_id: feed.id,
+            }),
+        }
+    }
+
+    (added, updated, removed)
+}
+
+// The starred entrys are added to the favorites, and the favorites of the remote feeds
+// which are no longer starred are removed. Return the added entrys and the removed uuids.
+fn merge_favorites(
+    local: &[RssConfig],
+    favorites: &[RssEntry],
+    starred: Vec<RssEntry>,
+) -> (Vec<RssEntry>, Vec<String>) {
+    let removed = favorites
+        .iter()
+        .filter(|item| {
+            local
+                .iter()
+                .any(|rss| !rss.remote_id.is_empty() && rss.uuid == item.suuid)
+        })
+        .filter(|item| !starred.iter().any(|entry| entry.uuid == item.uuid))
+        .map(|item| item.uuid.clone())
+        .collect();
+
+    let added = starred
+        .into_iter()
+        .filter(|entry| !favorites.iter().any(|item| item.uuid == entry.uuid))
+        .collect();
+
+    (added, removed)
+}
+
+async fn _sync_feeds(local: Vec<RssConfig>) -> Result<MergedFeeds> {
+    let service = service().await?;
+    if let Err(e) = service.refresh().await {
+        log::warn!("{e:?}");
+    }
+
+    let feeds = service.feeds().await?;
+    let (added, updated, removed) = merge_feeds(&local, feeds);
+
+    for item in added.iter() {
+        let config = serde_json::to_string(&item)?;
+        db::repo().insert_rss(&item.uuid, &config).await?;
+        db::repo().new_entry_list(&item.uuid).await?;
+    }
+
+    for item in updated.iter() {
+        let config = serde_json::to_string(&item)?;
+        db::repo().update_rss(&item.uuid, &config).await?;
+    }
+
+    for item in removed.iter() {
+        db::repo().delete_rss(&item.uuid).await?;
+        db::repo().drop_entry_list(&item.uuid).await?;
+    }
+
+    Ok((added, updated, removed))
+}
+
+async fn _fetch_starred_entrys(local: Vec<RssConfig>) -> Result<Vec<RssEntry>> {
+    let entrys = service().await?.starred_entries().await?;
+
+    Ok(entrys
+        .into_iter()
+        .filter_map(|entry| {
+            local
+                .iter()
+                .find(|item| !item.remote_id.is_empty() && item.remote_id == entry.feed_id)
+                .map(|item| to_rss_entry(&item.uuid, entry))
+        })
+        .collect())
+}
+
+fn update_rss_lists(ui: &AppWindow, (added, updated, removed): MergedFeeds) {
+    for item in removed.iter() {
+        super::rss::pop_rss(ui, &item.uuid);
+    }
+
+    for item in updated.into_iter() {
+        for (index, mut rss) in ui.global::<Store>().get_rss_lists().iter().enumerate() {
+            if rss.uuid != item.uuid {
+                continue;
+            }
+
+            rss.remote_id = item.remote_id.into();
+            ui.global::<Store>()
+                .get_rss_lists()
+                .set_row_data(index, rss);
+            break;
+        }
+    }
+
+    if added.is_empty() {
+        return;
+    }
+
+    let is_empty = ui.global::<Store>().get_rss_lists().row_count() == 0;
+    for item in added.into_iter() {
+        store_rss_lists!(ui).push(item.into());
+    }
+    super::rss::rss_config_sort(ui);
+
+    if is_empty {
+        let rss = ui.global::<Store>().get_rss_lists().row_data(0).unwrap();
+        ui.global::<Store>().set_rss_entrys(rss.entry);
+        ui.global::<Store>().set_current_rss_uuid(rss.uuid);
+    }
+}
+
+fn update_favorite_entrys(ui: &AppWindow, local: &[RssConfig], starred: Vec<RssEntry>) {
+    let favorites = ui
+        .global::<Store>()
+        .get_rss_favorite_entrys()
+        .iter()
+        .map(RssEntry::from)
+        .collect::<Vec<_>>();
+
+    let (added, removed) = merge_favorites(local, &favorites, starred);
+
+    for uuid in removed.iter() {
+        if let Some(index) = ui
+            .global::<Store>()
+            .get_rss_favorite_entrys()
+            .iter()
+            .position(|item| item.uuid == uuid.as_str())
+        {
+            store_favorite_entrys!(ui).remove(index);
+        }
+    }
+
+    for entry in added.iter() {
+        store_favorite_entrys!(ui).insert(0, entry.clone().into());
+    }
+
+    tokio::spawn(async move {
+        for uuid in removed.into_iter() {
+            if let Err(e) = db::repo().delete_entry(FAVORITE_UUID, &uuid).await {
+                log::warn!("{e:?}");
+            }
+        }
+
+        for entry in added.into_iter() {
+            if let Err(e) = super::entry::insert_favorite_entry(entry).await {
+                log::warn!("{e:?}");
+            }
+        }
+    });
+}
+
+// Import the subscriptions and the starred entrys from the server, then sync every feed
+pub fn sync_all(ui: Weak<AppWindow>, local: Vec<RssConfig>) {
+    tokio::spawn(async move {
+        let merged = match _sync_feeds(local).await {
+            Ok(v) => v,
+            Err(e) => {
+                async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("同步账户失败"), tr("原因")),
+                );
+                (vec![], vec![], vec![])
+            }
+        };
+
+        let ui_handle = ui.clone();
+        let _ = slint::invoke_from_event_loop(move || {
+            let ui = ui_handle.unwrap();
+            update_rss_lists(&ui, merged);
+
+            let local = super::rss::get_rss_configs(&ui);
+            let ui = ui.as_weak();
+            tokio::spawn(async move {
+                // the favorites are kept if the starred entrys can't be fetched
+                let starred = _fetch_starred_entrys(local.clone())
+                    .await
+                    .map_err(|e| log::warn!("{e:?}"))
+                    .ok();
+
+                let _ = slint::invoke_from_event_loop(move || {
+                    let ui = ui.unwrap();
+                    if let Some(starred) = starred {
+                        update_favorite_entrys(&ui, &local, starred);
+                    }
+                    super::rss::sync_rss_items(&ui);
+                });
+            });
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rss_config(uuid: &str, url: &str, remote_id: &str) -> RssConfig {
+        RssConfig {
+            uuid: uuid.to_string(),
+            remote_id: remote_id.to_string(),
+            ..crate::engine::tests::rss_config(uuid, url)
+        }
+    }
+
+    fn remote_feed(id: &str, url: &str) -> RemoteFeed {
+        RemoteFeed {
+            id: id.to_string(),
+            title: format!("title-{id}"),
+            url: url.to_string(),
+            category: "Tech".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_feeds() {
+        let local = vec![
+            rss_config("uuid-1", "https://example.com/feed-1", "feed/1"),
+            rss_config("uuid-2", "https://www.example.com/feed-2/", ""),
+            rss_config("uuid-3", "https://example.com/feed-4", "feed/4"),
+            rss_config("uuid-4", "https://example.com/feed-5", ""),
+        ];
+
+        let remote = vec![
+            remote_feed("feed/1", "https://example.com/feed-1-moved"),
+            remote_feed("feed/2", "https://example.com/feed-2"),
+            remote_feed("feed/3", "https://example.com/feed-3"),
+        ];
+
+        let (added, updated, removed) = merge_feeds(&local, remote);
+
+        // the feed which is only in the device is kept
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].uuid, "uuid-3");
+
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].uuid, "uuid-2");
+        assert_eq!(updated[0].remote_id, "feed/2");
+
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].url, "https://example.com/feed-3");
+        assert_eq!(added[0].name, "title-feed/3");
+        assert_eq!(added[0].category, "Tech");
+        assert_eq!(added[0].remote_id, "feed/3");
+    }
+
+    fn rss_entry(suuid: &str, uuid: &str) -> RssEntry {
+        RssEntry {
+            suuid: suuid.to_string(),
+            ..crate::engine::tests::rss_entry(uuid, "")
+        }
+    }
+
+    #[test]
+    fn test_merge_favorites() {
+        let local = vec![
+            rss_config("uuid-1", "https://example.com/feed-1", "feed/1"),
+            rss_config("uuid-2", "https://example.com/feed-2", ""),
+        ];
+
+        let favorites = vec![
+            rss_entry("uuid-1", "entry/1"),
+            rss_entry("uuid-1", "entry/2"),
+            rss_entry("uuid-2", "entry-3"),
+        ];
+
+        let starred = vec![
+            rss_entry("uuid-1", "entry/1"),
+            rss_entry("uuid-1", "entry/4"),
+        ];
+
+        let (added, removed) = merge_favorites(&local, &favorites, starred);
+
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].uuid, "entry/4");
+
+        // the favorite of the direct feed is kept
+        assert_eq!(removed, vec!["entry/2".to_string()]);
+    }
+}
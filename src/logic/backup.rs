@@ -0,0 +1,1382 @@
+use super::{
+    entry,
+    message::{async_message_success, async_message_warn},
+    rss, setting,
+};
+use crate::slint_generatedAppWindow::{
+    AppWindow, LocalBackup as UILocalBackup, Logic, OkCancelDialogSetting, SettingBackupRecover,
+    Store,
+};
+use crate::{
+    config::{self, Config},
+    db::{self, entry::RssEntry, rss::RssConfig, trash::TrashEntry},
+    util::{
+        self,
+        crypto::{self, EncryptedData},
+        http::{self, ProxyType},
+        time::local_now,
+        translator::tr,
+        url,
+        webdav::WebDav,
+    },
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use slint::{ComponentHandle, Model, VecModel, Weak};
+use std::{collections::HashSet, fs, path::Path, sync::Mutex, time::Duration};
+use uuid::Uuid;
+
+// 1: rss, collection and setting, the archives have no `version` field.
+// 2: add read state, blacklist and trash.
+// 3: the archive can be encrypted by a passphrase.
+// 4: add the entrys of the rss.
+pub const BACKUP_VERSION: u32 = 4;
+
+const LOCAL_BACKUP_PREFIX: &str = "rssbox-backup-";
+const LOCAL_BACKUP_SUFFIX: &str = ".json";
+
+// enough to hold the marker of an encrypted archive
+const BACKUP_HEAD_SIZE: usize = 64;
+
+const ENCRYPTED_MARKER: &str = "rssbox-encrypted";
+
+// The auto backups are rotated, and the manual backups are never removed
+const AUTO_BACKUP_SUFFIX: &str = "-auto.json";
+
+// The fetched backup which is waiting for the user to confirm the recover plan
+static PENDING_RECOVER: Lazy<Mutex<Option<(BackupRecoverData, SettingBackupRecover)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+#[macro_export]
+macro_rules! store_webdav_backups {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_webdav_backups()
+            .as_any()
+            .downcast_ref::<VecModel<UILocalBackup>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+#[macro_export]
+macro_rules! store_local_backups {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_local_backups()
+            .as_any()
+            .downcast_ref::<VecModel<UILocalBackup>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+// The read entrys of a rss. Use the rss url as the key, because the rss uuid may be changed.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ReadState {
+    pub rss_url: String,
+    pub entrys: Vec<RssEntry>,
+}
+
+// All the entrys of a rss, including the unread ones
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct RssEntrys {
+    pub rss_url: String,
+    pub entrys: Vec<RssEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupRecoverData {
+    #[serde(default = "version_default")]
+    pub version: u32,
+
+    #[serde(default)]
+    pub rss: Vec<RssConfig>,
+
+    #[serde(default)]
+    pub collection: Vec<RssEntry>,
+
+    #[serde(default)]
+    pub setting: Option<Config>,
+
+    #[serde(default)]
+    pub read_state: Vec<ReadState>,
+
+    #[serde(default)]
+    pub blacklist: Vec<String>,
+
+    #[serde(default)]
+    pub trash: Vec<TrashEntry>,
+
+    #[serde(default)]
+    pub entrys: Vec<RssEntrys>,
+}
+
+fn version_default() -> u32 {
+    1
+}
+
+impl Default for BackupRecoverData {
+    fn default() -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            rss: vec![],
+            collection: vec![],
+            setting: None,
+            read_state: vec![],
+            blacklist: vec![],
+            trash: vec![],
+            entrys: vec![],
+        }
+    }
+}
+
+// The archive which is encrypted by a passphrase. The server only stores it as it is.
+// The marker is the first field, so that an archive can be recognized by its head.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct EncryptedBackup {
+    #[serde(default)]
+    marker: String,
+
+    version: u32,
+    encrypted: EncryptedData,
+}
+
+pub fn is_encrypted(text: &str) -> bool {
+    serde_json::from_str::<EncryptedBackup>(text).is_ok()
+}
+
+// Only the head is needed, because the marker is written first
+pub fn is_encrypted_head(head: &str) -> bool {
+    let head = head
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    head.starts_with(&format!(r#"{{"marker":"{ENCRYPTED_MARKER}""#))
+}
+
+// What will be changed by a merge recover. The subscriptions are matched by the normalized url.
+#[derive(Debug, Default, Clone)]
+pub struct RecoverPlan {
+    pub added_rss: Vec<RssConfig>,
+
+    // the uuids are the local ones, so that the entrys are kept
+    pub updated_rss: Vec<RssConfig>,
+
+    // the local subscriptions which are not in the backup, only when the removal is enabled
+    pub removed_rss: Vec<RssConfig>,
+
+    pub added_favorites: Vec<RssEntry>,
+}
+
+impl RecoverPlan {
+    fn summary(&self, options: &SettingBackupRecover) -> String {
+        let mut lines = vec![];
+
+        if options.rss {
+            lines.push(format!("{}: {}", tr("新增订阅"), self.added_rss.len()));
+            lines.push(format!("{}: {}", tr("更新订阅"), self.updated_rss.len()));
+            if options.remove_rss {
+                lines.push(format!("{}: {}", tr("删除订阅"), self.removed_rss.len()));
+            }
+        }
+
+        if options.favorite {
+            lines.push(format!(
+                "{}: {}",
+                tr("新增收藏"),
+                self.added_favorites.len()
+            ));
+        }
+
+        lines.push(tr("是否恢复？"));
+        lines.join("\n")
+    }
+}
+
+impl BackupRecoverData {
+    pub fn from_str(text: &str) -> Result<Self> {
+        let data = serde_json::from_str::<BackupRecoverData>(text)?;
+        data.check_version()?;
+        Ok(data)
+    }
+
+    // Encrypt the archive if the passphrase is not empty
+    pub fn to_text(&self, passphrase: &str) -> Result<String> {
+        if passphrase.is_empty() {
+            return Ok(serde_json::to_string_pretty(self)?);
+        }
+
+        let text = serde_json::to_string(self)?;
+        let backup = EncryptedBackup {
+            marker: ENCRYPTED_MARKER.to_string(),
+            version: BACKUP_VERSION,
+            encrypted: crypto::encrypt(passphrase, text.as_bytes())?,
+        };
+
+        Ok(serde_json::to_string_pretty(&backup)?)
+    }
+
+    pub fn from_text(text: &str, passphrase: &str) -> Result<Self> {
+        let Ok(backup) = serde_json::from_str::<EncryptedBackup>(text) else {
+            return Self::from_str(text);
+        };
+
+        if backup.version > BACKUP_VERSION {
+            bail!(
+                "{}: {} > {}",
+                tr("不支持的备份版本"),
+                backup.version,
+                BACKUP_VERSION
+            );
+        }
+
+        if passphrase.is_empty() {
+            bail!(tr("备份已加密，请输入密码"));
+        }
+
+        let plaintext = crypto::decrypt(passphrase, &backup.encrypted)
+            .map_err(|_| anyhow!(tr("密码错误或备份已损坏")))?;
+
+        Self::from_str(&String::from_utf8(plaintext)?)
+    }
+
+    pub fn check_version(&self) -> Result<()> {
+        if self.version > BACKUP_VERSION {
+            anyhow::bail!(
+                "{}: {} > {}",
+                tr("不支持的备份版本"),
+                self.version,
+                BACKUP_VERSION
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn init(ui: &AppWindow) {
+    store_local_backups!(ui).set_vec(vec![]);
+    store_webdav_backups!(ui).set_vec(vec![]);
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_backup_to_remote(move |options| {
+        let ui = ui_handle.unwrap();
+        let data = backup_data_from_ui(&ui, &options);
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            let res = match backup_data_from_db(data, options.clone()).await {
+                Ok(data) => match data.to_text(&options.passphrase) {
+                    Ok(text) => _send_backup_to_remote(options.api_token.into(), text).await,
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("备份失败"), tr("原因")),
+                ),
+                _ => async_message_success(ui.clone(), tr("备份成功")),
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_recover_from_remote(move |options| {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            let res = match _fetch_backup_from_remote(
+                options.api_token.to_string(),
+                options.passphrase.to_string(),
+            )
+            .await
+            {
+                Ok(data) => prepare_recover(ui.clone(), data, options).await,
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = res {
+                async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+                );
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_backup_to_local(move |options| {
+        let ui = ui_handle.unwrap();
+        let data = backup_data_from_ui(&ui, &options);
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            let passphrase = options.passphrase.to_string();
+            let res = match backup_data_from_db(data, options).await {
+                Ok(data) => save_local_backup(&config::backup_dir(), &data, &passphrase),
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("备份失败"), tr("原因")),
+                ),
+                Ok(name) => {
+                    async_message_success(ui.clone(), format!("{}: {name}", tr("备份成功")));
+                    let _ = slint::invoke_from_event_loop(move || {
+                        ui.unwrap().global::<Logic>().invoke_load_local_backups();
+                    });
+                }
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>()
+        .on_recover_from_local(move |options, name| {
+            let ui = ui_handle.clone();
+            tokio::spawn(async move {
+                let res = match load_local_backup(&config::backup_dir(), &name, &options.passphrase)
+                {
+                    Ok(data) => prepare_recover(ui.clone(), data, options).await,
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = res {
+                    async_message_warn(
+                        ui.clone(),
+                        format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+                    );
+                }
+            });
+        });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_apply_recover(move || {
+        let Some((data, options)) = PENDING_RECOVER.lock().unwrap().take() else {
+            return;
+        };
+
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match recover(ui.clone(), data, options).await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+                ),
+                _ => async_message_success(ui.clone(), tr("恢复成功")),
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_auto_backup(move || {
+        let ui = ui_handle.unwrap();
+        let options = ui.global::<Logic>().invoke_get_setting_backup_recover();
+        let data = backup_data_from_ui(&ui, &options);
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            let res = auto_backup(data, options).await;
+            record_auto_backup(&res);
+
+            match res {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("自动备份失败"), tr("原因")),
+                ),
+                Ok(name) => log::info!("auto backup success: {name}"),
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_load_local_backups(move || {
+        let ui = ui_handle.unwrap();
+        match list_local_backups(&config::backup_dir()) {
+            Ok(items) => store_local_backups!(ui).set_vec(items),
+            Err(e) => {
+                log::warn!("{e:?}");
+                store_local_backups!(ui).set_vec(vec![]);
+            }
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_remove_local_backup(move |name| {
+        let ui = ui_handle.unwrap();
+
+        match fs::remove_file(config::backup_dir().join(name.as_str())) {
+            Err(e) => async_message_warn(
+                ui.as_weak(),
+                format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
+            ),
+            _ => {
+                for (index, item) in ui.global::<Store>().get_local_backups().iter().enumerate() {
+                    if item.name == name {
+                        store_local_backups!(ui).remove(index);
+                        break;
+                    }
+                }
+                async_message_success(ui.as_weak(), tr("删除成功"));
+            }
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_backup_to_webdav(move |options| {
+        let ui = ui_handle.unwrap();
+        let data = backup_data_from_ui(&ui, &options);
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            let passphrase = options.passphrase.to_string();
+            let res = match backup_data_from_db(data, options).await {
+                Ok(data) => match data.to_text(&passphrase) {
+                    Ok(text) => {
+                        let name = backup_name();
+                        webdav().put(&name, text).await.map(|_| name)
+                    }
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("备份失败"), tr("原因")),
+                ),
+                Ok(name) => {
+                    async_message_success(ui.clone(), format!("{}: {name}", tr("备份成功")));
+                    let _ = slint::invoke_from_event_loop(move || {
+                        ui.unwrap().global::<Logic>().invoke_load_webdav_backups();
+                    });
+                }
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>()
+        .on_recover_from_webdav(move |options, name| {
+            let ui = ui_handle.clone();
+            tokio::spawn(async move {
+                let res = match webdav().get(&name).await {
+                    Ok(text) => match BackupRecoverData::from_text(&text, &options.passphrase) {
+                        Ok(data) => prepare_recover(ui.clone(), data, options).await,
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = res {
+                    async_message_warn(
+                        ui.clone(),
+                        format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+                    );
+                }
+            });
+        });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_load_webdav_backups(move || {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match list_webdav_backups().await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("加载失败"), tr("原因")),
+                ),
+                Ok(items) => {
+                    let _ = slint::invoke_from_event_loop(move || {
+                        store_webdav_backups!(ui.unwrap()).set_vec(items);
+                    });
+                }
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_remove_webdav_backup(move |name| {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match webdav().delete(&name).await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
+                ),
+                _ => {
+                    async_message_success(ui.clone(), tr("删除成功"));
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let ui = ui.unwrap();
+                        for (index, item) in
+                            ui.global::<Store>().get_webdav_backups().iter().enumerate()
+                        {
+                            if item.name == name {
+                                store_webdav_backups!(ui).remove(index);
+                                break;
+                            }
+                        }
+                    });
+                }
+            }
+        });
+    });
+}
+
+fn backup_name() -> String {
+    format!(
+        "{LOCAL_BACKUP_PREFIX}{}{LOCAL_BACKUP_SUFFIX}",
+        local_now("%Y%m%d-%H%M%S")
+    )
+}
+
+fn auto_backup_name() -> String {
+    format!(
+        "{LOCAL_BACKUP_PREFIX}{}{AUTO_BACKUP_SUFFIX}",
+        local_now("%Y%m%d-%H%M%S")
+    )
+}
+
+// The auto backups which exceed the keep counts, the latest ones are kept
+fn expired_auto_backups(mut names: Vec<String>, keep: usize) -> Vec<String> {
+    names
+        .retain(|name| name.starts_with(LOCAL_BACKUP_PREFIX) && name.ends_with(AUTO_BACKUP_SUFFIX));
+    names.sort_by(|a, b| b.cmp(a));
+    names.into_iter().skip(usize::max(keep, 1)).collect()
+}
+
+// Return the name of the backup, the remote server keeps only one backup and has no name
+async fn auto_backup(data: BackupRecoverData, options: SettingBackupRecover) -> Result<String> {
+    let conf = config::backup_recover();
+    let keep = conf.auto_backup_keep as usize;
+    let data = backup_data_from_db(data, options.clone()).await?;
+    let text = data.to_text(&options.passphrase)?;
+
+    match conf.auto_backup_destination.as_str() {
+        "Remote" => {
+            _send_backup_to_remote(options.api_token.into(), text).await?;
+            Ok(String::default())
+        }
+        "WebDAV" => {
+            let dav = webdav();
+            let name = auto_backup_name();
+            dav.put(&name, text).await?;
+
+            let names = dav
+                .list()
+                .await?
+                .into_iter()
+                .map(|item| item.name)
+                .collect();
+            for name in expired_auto_backups(names, keep) {
+                dav.delete(&name).await?;
+            }
+            Ok(name)
+        }
+        _ => {
+            let dir = config::backup_dir();
+            fs::create_dir_all(&dir)?;
+
+            let name = auto_backup_name();
+            fs::write(dir.join(&name), text)?;
+
+            let names = fs::read_dir(&dir)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect();
+            for name in expired_auto_backups(names, keep) {
+                fs::remove_file(dir.join(name))?;
+            }
+            Ok(name)
+        }
+    }
+}
+
+fn record_auto_backup(res: &Result<String>) {
+    let mut all = config::all();
+
+    match res {
+        Ok(_) => {
+            all.backup_recover.last_auto_backup_time = Utc::now().timestamp();
+            all.backup_recover.last_auto_backup_error.clear();
+        }
+        Err(e) => all.backup_recover.last_auto_backup_error = format!("{e}"),
+    }
+
+    if let Err(e) = config::save(all) {
+        log::warn!("{e:?}");
+    }
+}
+
+fn webdav() -> WebDav {
+    let config = config::webdav();
+    let proxy_type = if config.use_http_proxy {
+        Some(ProxyType::Http)
+    } else if config.use_socks5_proxy {
+        Some(ProxyType::Socks5)
+    } else {
+        None
+    };
+
+    WebDav::new(&config.url, &config.username, &config.password, proxy_type)
+}
+
+// the latest backup is the first one
+async fn list_webdav_backups() -> Result<Vec<UILocalBackup>> {
+    let dav = webdav();
+
+    let mut items = vec![];
+    for item in dav.list().await?.into_iter() {
+        if !item.name.starts_with(LOCAL_BACKUP_PREFIX) || !item.name.ends_with(LOCAL_BACKUP_SUFFIX)
+        {
+            continue;
+        }
+
+        let is_encrypted = match dav.get_head(&item.name, BACKUP_HEAD_SIZE).await {
+            Ok(head) => is_encrypted_head(&head),
+            Err(e) => {
+                log::warn!("{e:?}");
+                false
+            }
+        };
+
+        items.push(UILocalBackup {
+            name: item.name.into(),
+            size: util::str::pretty_size_string(item.size).into(),
+            is_encrypted,
+        });
+    }
+
+    items.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(items)
+}
+
+// the data which is only in the ui
+fn backup_data_from_ui(ui: &AppWindow, options: &SettingBackupRecover) -> BackupRecoverData {
+    let mut data = BackupRecoverData::default();
+
+    if options.rss {
+        data.rss = rss::get_rss_configs(ui);
+    }
+
+    if options.favorite {
+        data.collection = entry::get_favorite_entrys(ui);
+    }
+
+    if options.setting {
+        // the credentials should not leave the device
+        let mut setting = config::all();
+        setting.backup_recover.api_token.clear();
+        setting.backup_recover.passphrase.clear();
+        setting.webdav.password.clear();
+        setting.account.password.clear();
+        setting.local_api.token.clear();
+        data.setting = Some(setting);
+    }
+
+    if options.read_state {
+        data.read_state = ui
+            .global::<Store>()
+            .get_rss_lists()
+            .iter()
+            .map(|rss| ReadState {
+                rss_url: rss.url.into(),
+                entrys: rss
+                    .entry
+                    .iter()
+                    .filter(|item| item.is_read)
+                    .map(|item| item.into())
+                    .collect(),
+            })
+            .filter(|item| !item.entrys.is_empty())
+            .collect();
+    }
+
+    if options.entrys {
+        data.entrys = ui
+            .global::<Store>()
+            .get_rss_lists()
+            .iter()
+            .map(|rss| RssEntrys {
+                rss_url: rss.url.into(),
+                entrys: rss.entry.iter().map(|item| item.into()).collect(),
+            })
+            .filter(|item| !item.entrys.is_empty())
+            .collect();
+    }
+
+    data
+}
+
+async fn backup_data_from_db(
+    mut data: BackupRecoverData,
+    options: SettingBackupRecover,
+) -> Result<BackupRecoverData> {
+    if options.blacklist {
+        data.blacklist = db::repo().select_all_blacklist().await?;
+    }
+
+    if options.trash {
+        data.trash = db::repo().select_all_trash().await?;
+    }
+
+    Ok(data)
+}
+
+async fn recover(
+    ui: Weak<AppWindow>,
+    data: BackupRecoverData,
+    options: SettingBackupRecover,
+) -> Result<()> {
+    data.check_version()?;
+
+    if options.setting {
+        if let Some(mut setting) = data.setting {
+            let current = config::backup_recover();
+            setting.backup_recover.api_token = current.api_token;
+            setting.backup_recover.passphrase = current.passphrase;
+            setting.webdav = config::webdav();
+
+            // the account and its pulled items belong to the device
+            setting.account = config::account();
+            setting.local_api = config::local_api();
+            config::reset(setting);
+
+            let ui = ui.clone();
+            let _ = slint::invoke_from_event_loop(move || {
+                setting::init_setting(&ui.unwrap());
+            });
+        }
+    }
+
+    if options.rss {
+        let local = local_rss().await?;
+        let plan = merge_rss(&local, data.rss, options.remove_rss);
+        recover_rss(plan).await?;
+    }
+
+    if options.entrys {
+        let items = data
+            .entrys
+            .into_iter()
+            .map(|item| (item.rss_url, item.entrys))
+            .collect();
+        recover_entrys(items, false).await?;
+    }
+
+    if options.read_state {
+        let items = data
+            .read_state
+            .into_iter()
+            .map(|item| (item.rss_url, item.entrys))
+            .collect();
+        recover_entrys(items, true).await?;
+    }
+
+    if options.favorite {
+        let local = local_favorites().await?;
+        for item in merge_favorites(&local, data.collection).into_iter() {
+            let text = serde_json::to_string(&item)?;
+            db::repo()
+                .insert_entry(entry::FAVORITE_UUID, &item.uuid, &text)
+                .await?;
+        }
+    }
+
+    if options.blacklist {
+        for md5 in data.blacklist.iter() {
+            if db::repo().is_exist_blacklist(md5).await.is_err() {
+                db::repo().insert_blacklist(md5).await?;
+            }
+        }
+    }
+
+    if options.trash {
+        for item in data.trash.iter() {
+            db::repo().restore_trash(item).await?;
+        }
+    }
+
+    let _ = slint::invoke_from_event_loop(move || {
+        let ui = ui.unwrap();
+        if options.rss || options.read_state || options.entrys {
+            ui.global::<Logic>().invoke_load_all_rss();
+        }
+
+        if options.favorite {
+            entry::init_favorite(ui.as_weak());
+        }
+    });
+
+    Ok(())
+}
+
+async fn prepare_recover(
+    ui: Weak<AppWindow>,
+    data: BackupRecoverData,
+    options: SettingBackupRecover,
+) -> Result<()> {
+    data.check_version()?;
+
+    let mut plan = RecoverPlan::default();
+    if options.rss {
+        plan = merge_rss(&local_rss().await?, data.rss.clone(), options.remove_rss);
+    }
+
+    if options.favorite {
+        plan.added_favorites = merge_favorites(&local_favorites().await?, data.collection.clone());
+    }
+
+    let summary = plan.summary(&options);
+    *PENDING_RECOVER.lock().unwrap() = Some((data, options));
+
+    let _ = slint::invoke_from_event_loop(move || {
+        let ui = ui.unwrap();
+        let dialog = ui.global::<OkCancelDialogSetting>();
+        dialog.set_title_type("warning".into());
+        dialog.set_title_text(tr("恢复计划").into());
+        dialog.set_handle_type("apply-recover".into());
+        dialog.set_handle_uuid("".into());
+        dialog.set_body_text(summary.into());
+    });
+
+    Ok(())
+}
+
+async fn local_rss() -> Result<Vec<RssConfig>> {
+    Ok(db::repo()
+        .select_all_rss()
+        .await?
+        .into_iter()
+        .filter_map(|item| serde_json::from_str::<RssConfig>(&item.data).ok())
+        .collect())
+}
+
+async fn local_favorites() -> Result<Vec<RssEntry>> {
+    Ok(db::repo()
+        .select_all_entrys(entry::FAVORITE_UUID)
+        .await?
+        .into_iter()
+        .filter_map(|item| serde_json::from_str::<RssEntry>(&item.data).ok())
+        .collect())
+}
+
+fn is_rss_changed(a: &RssConfig, b: &RssConfig) -> bool {
+    a.name != b.name
+        || a.url != b.url
+        || a.icon_index != b.icon_index
+        || a.use_http_proxy != b.use_http_proxy
+        || a.use_socks5_proxy != b.use_socks5_proxy
+        || a.is_favorite != b.is_favorite
+        || a.feed_format != b.feed_format
+        || a.category != b.category
+}
+
+// A merge only adds and updates the subscriptions unless `is_remove` is set
+fn merge_rss(local: &[RssConfig], backup: Vec<RssConfig>, is_remove: bool) -> RecoverPlan {
+    let mut plan = RecoverPlan::default();
+    let mut urls = HashSet::new();
+
+    for mut item in backup.into_iter() {
+        let key = url::normalize(&item.url);
+        if !urls.insert(key.clone()) {
+            continue;
+        }
+
+        match local.iter().find(|rss| url::normalize(&rss.url) == key) {
+            Some(rss) => {
+                // keep the local url, such as not to downgrade `https` to `http`
+                item.uuid = rss.uuid.clone();
+                item.url = rss.url.clone();
+                if is_rss_changed(rss, &item) {
+                    plan.updated_rss.push(item);
+                }
+            }
+            _ => {
+                if item.uuid.is_empty() || local.iter().any(|rss| rss.uuid == item.uuid) {
+                    item.uuid = Uuid::new_v4().to_string();
+                }
+                plan.added_rss.push(item);
+            }
+        }
+    }
+
+    if is_remove {
+        plan.removed_rss = local
+            .iter()
+            .filter(|rss| !urls.contains(&url::normalize(&rss.url)))
+            .cloned()
+            .collect();
+    }
+
+    plan
+}
+
+// Return the favorites which should be added. The favorites are identified by the normalized url.
+fn merge_favorites(local: &[RssEntry], backup: Vec<RssEntry>) -> Vec<RssEntry> {
+    let mut urls = local
+        .iter()
+        .map(|item| url::normalize(&item.url))
+        .collect::<HashSet<_>>();
+    let uuids = local
+        .iter()
+        .map(|item| item.uuid.clone())
+        .collect::<HashSet<_>>();
+
+    let mut items = vec![];
+    for mut item in backup.into_iter() {
+        if !urls.insert(url::normalize(&item.url)) {
+            continue;
+        }
+
+        if item.uuid.is_empty() || uuids.contains(&item.uuid) {
+            item.uuid = Uuid::new_v4().to_string();
+        }
+
+        items.push(item);
+    }
+
+    items
+}
+
+// The entrys of the kept and updated subscriptions are not touched
+async fn recover_rss(plan: RecoverPlan) -> Result<()> {
+    for item in plan.removed_rss.into_iter() {
+        _ = db::repo().drop_entry_list(&item.uuid).await;
+        db::repo().delete_rss(&item.uuid).await?;
+    }
+
+    for item in plan.updated_rss.into_iter() {
+        let config = serde_json::to_string(&item)?;
+        db::repo().update_rss(&item.uuid, &config).await?;
+    }
+
+    for item in plan.added_rss.into_iter() {
+        let config = serde_json::to_string(&item)?;
+        db::repo().insert_rss(&item.uuid, &config).await?;
+        db::repo().new_entry_list(&item.uuid).await?;
+    }
+
+    Ok(())
+}
+
+// Put the read entrys back, so that they won't be shown as unread after syncing
+// Return the entrys to insert and the local entrys to mark as read. The entrys are matched by url.
+fn merge_entrys(
+    local: &[RssEntry],
+    backup: Vec<RssEntry>,
+    is_read_state: bool,
+) -> (Vec<RssEntry>, Vec<RssEntry>) {
+    let (mut inserts, mut updates) = (vec![], vec![]);
+
+    for mut entry in backup.into_iter() {
+        match local.iter().find(|item| item.url == entry.url) {
+            Some(item) => {
+                if entry.is_read
+                    && !item.is_read
+                    && !updates.iter().any(|u: &RssEntry| u.url == item.url)
+                {
+                    let mut item = item.clone();
+                    item.is_read = true;
+                    updates.push(item);
+                }
+            }
+            _ => {
+                if inserts.iter().any(|i: &RssEntry| i.url == entry.url) {
+                    continue;
+                }
+
+                if is_read_state {
+                    entry.is_read = true;
+                }
+                inserts.push(entry);
+            }
+        }
+    }
+
+    (inserts, updates)
+}
+
+// The items are the rss urls and their entrys
+async fn recover_entrys(items: Vec<(String, Vec<RssEntry>)>, is_read_state: bool) -> Result<()> {
+    let rss_list = db::repo()
+        .select_all_rss()
+        .await?
+        .into_iter()
+        .filter_map(|item| serde_json::from_str::<RssConfig>(&item.data).ok())
+        .collect::<Vec<_>>();
+
+    for (rss_url, entrys) in items.into_iter() {
+        let Some(rss) = rss_list.iter().find(|rss| url::is_same(&rss.url, &rss_url)) else {
+            continue;
+        };
+
+        let local = db::repo()
+            .select_all_entrys(&rss.uuid)
+            .await?
+            .into_iter()
+            .filter_map(|item| serde_json::from_str::<RssEntry>(&item.data).ok())
+            .collect::<Vec<_>>();
+
+        let (inserts, updates) = merge_entrys(&local, entrys, is_read_state);
+
+        for mut entry in inserts.into_iter() {
+            entry.suuid = rss.uuid.clone();
+            if entry.uuid.is_empty() {
+                entry.uuid = Uuid::new_v4().to_string();
+            }
+
+            let text = serde_json::to_string(&entry)?;
+            db::repo()
+                .insert_entry(&rss.uuid, &entry.uuid, &text)
+                .await?;
+        }
+
+        for entry in updates.into_iter() {
+            let text = serde_json::to_string(&entry)?;
+            db::repo()
+                .update_entry(&rss.uuid, &entry.uuid, &text)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn save_local_backup(dir: &Path, data: &BackupRecoverData, passphrase: &str) -> Result<String> {
+    fs::create_dir_all(dir)?;
+
+    let name = backup_name();
+    fs::write(dir.join(&name), data.to_text(passphrase)?)?;
+
+    Ok(name)
+}
+
+pub fn load_local_backup(dir: &Path, name: &str, passphrase: &str) -> Result<BackupRecoverData> {
+    let path = dir.join(name);
+    let text = fs::read_to_string(&path).with_context(|| format!("read {:?} failed", path))?;
+    BackupRecoverData::from_text(&text, passphrase)
+}
+
+// the latest backup is the first one
+fn list_local_backups(dir: &Path) -> Result<Vec<UILocalBackup>> {
+    let mut items = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(LOCAL_BACKUP_PREFIX) || !name.ends_with(LOCAL_BACKUP_SUFFIX) {
+            continue;
+        }
+
+        let size = entry.metadata().map(|md| md.len()).unwrap_or_default();
+        let is_encrypted = fs::read_to_string(entry.path())
+            .map(|text| is_encrypted(&text))
+            .unwrap_or_default();
+
+        items.push(UILocalBackup {
+            name: name.into(),
+            size: util::str::pretty_size_string(size).into(),
+            is_encrypted,
+        });
+    }
+
+    items.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(items)
+}
+
+// The api token is only sent in the header, so that it won't be recorded in the access logs
+async fn _send_backup_to_remote(api_token: String, text: String) -> Result<()> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+    headers.insert(
+        AUTHORIZATION,
+        format!("Bearer {api_token}").parse().unwrap(),
+    );
+
+    let res = http::client(None)?
+        .post(config::endpoint().backup_url())
+        .timeout(Duration::from_secs(15))
+        .headers(headers)
+        .body(text)
+        .send()
+        .await?;
+
+    if !res.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "http error code: {}",
+            res.status().as_str()
+        ));
+    }
+
+    Ok(())
+}
+
+async fn _fetch_backup_from_remote(
+    api_token: String,
+    passphrase: String,
+) -> Result<BackupRecoverData> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        format!("Bearer {api_token}").parse().unwrap(),
+    );
+
+    let res = http::client(None)?
+        .get(config::endpoint().recover_url())
+        .timeout(Duration::from_secs(15))
+        .headers(headers)
+        .send()
+        .await?;
+
+    if !res.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "http error code: {}",
+            res.status().as_str()
+        ));
+    }
+
+    BackupRecoverData::from_text(&res.text().await?, &passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_version_1() -> Result<()> {
+        let text = r#"{
+            "rss": [{
+                "uuid": "uuid-1",
+                "name": "name-1",
+                "url": "https://example.com/feed",
+                "icon_index": 0,
+                "use_http_proxy": false,
+                "use_socks5_proxy": false,
+                "is_favorite": false
+            }],
+            "collection": []
+        }"#;
+
+        let data = BackupRecoverData::from_str(text)?;
+        assert_eq!(data.version, 1);
+        assert_eq!(data.rss.len(), 1);
+        assert!(data.setting.is_none());
+        assert!(data.read_state.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_version_3() -> Result<()> {
+        let text = r#"{
+            "version": 3,
+            "rss": [],
+            "collection": [],
+            "read_state": [{"rss_url": "https://example.com/feed", "entrys": []}],
+            "blacklist": ["md5-1"],
+            "trash": []
+        }"#;
+
+        let data = BackupRecoverData::from_str(text)?;
+        assert_eq!(data.version, 3);
+        assert_eq!(data.read_state.len(), 1);
+        assert!(data.entrys.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_entrys() {
+        let entry = |url: &str, is_read: bool| RssEntry {
+            uuid: url.to_string(),
+            url: url.to_string(),
+            is_read,
+            ..Default::default()
+        };
+
+        let local = vec![entry("url-1", false), entry("url-2", true)];
+        let backup = vec![
+            entry("url-1", true),
+            entry("url-2", false),
+            entry("url-3", false),
+            entry("url-3", false),
+        ];
+
+        let (inserts, updates) = merge_entrys(&local, backup.clone(), false);
+        assert_eq!(inserts.len(), 1);
+        assert!(!inserts[0].is_read);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].url, "url-1");
+        assert!(updates[0].is_read);
+
+        let (inserts, _) = merge_entrys(&local, backup, true);
+        assert!(inserts[0].is_read);
+    }
+
+    #[test]
+    fn test_recover_newer_version() {
+        let text = format!(r#"{{"version": {}}}"#, BACKUP_VERSION + 1);
+        assert!(BackupRecoverData::from_str(&text).is_err());
+    }
+
+    #[test]
+    fn test_local_backup() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("rssbox-backup-test-{}", Uuid::new_v4()));
+
+        let data = BackupRecoverData {
+            blacklist: vec!["md5-1".to_string()],
+            read_state: vec![ReadState {
+                rss_url: "https://example.com/feed".to_string(),
+                entrys: vec![RssEntry::default()],
+            }],
+            entrys: vec![RssEntrys {
+                rss_url: "https://example.com/feed".to_string(),
+                entrys: vec![RssEntry::default(), RssEntry::default()],
+            }],
+            ..Default::default()
+        };
+
+        let name = save_local_backup(&dir, &data, "")?;
+        assert_eq!(list_local_backups(&dir)?.len(), 1);
+
+        let data = load_local_backup(&dir, &name, "")?;
+        assert_eq!(data.version, BACKUP_VERSION);
+        assert_eq!(data.blacklist, vec!["md5-1"]);
+        assert_eq!(data.read_state.len(), 1);
+        assert_eq!(data.entrys[0].entrys.len(), 2);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_backup() -> Result<()> {
+        let data = BackupRecoverData {
+            blacklist: vec!["md5-1".to_string()],
+            ..Default::default()
+        };
+
+        let text = data.to_text("passphrase-1")?;
+        assert!(is_encrypted(&text));
+        assert!(is_encrypted_head(&text[..BACKUP_HEAD_SIZE]));
+        assert!(!is_encrypted_head(
+            r#"{"version":4,"marker":"rssbox-encrypted"}"#
+        ));
+        assert!(!text.contains("md5-1"));
+
+        assert!(BackupRecoverData::from_text(&text, "").is_err());
+        assert!(BackupRecoverData::from_text(&text, "passphrase-2").is_err());
+
+        let data = BackupRecoverData::from_text(&text, "passphrase-1")?;
+        assert_eq!(data.blacklist, vec!["md5-1"]);
+
+        // the plain archive is still supported
+        let text = data.to_text("")?;
+        assert!(!is_encrypted(&text));
+        assert!(!is_encrypted_head(&text[..BACKUP_HEAD_SIZE]));
+        assert_eq!(
+            BackupRecoverData::from_text(&text, "passphrase-1")?
+                .blacklist
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    fn rss_config(uuid: &str, name: &str, url: &str) -> RssConfig {
+        RssConfig {
+            uuid: uuid.to_string(),
+            ..crate::engine::tests::rss_config(name, url)
+        }
+    }
+
+    #[test]
+    fn test_merge_rss() {
+        let local = vec![
+            rss_config("uuid-1", "name-1", "https://example.com/feed-1"),
+            rss_config("uuid-2", "name-2", "https://example.com/feed-2"),
+            rss_config("uuid-3", "name-3", "https://example.com/feed-3"),
+        ];
+
+        let backup = vec![
+            rss_config("uuid-a", "name-1", "http://www.example.com/feed-1/"),
+            rss_config("uuid-b", "name-2-new", "https://example.com/feed-2"),
+            rss_config("uuid-3", "name-4", "https://example.com/feed-4"),
+            rss_config("uuid-c", "name-4", "https://example.com/feed-4"),
+        ];
+
+        let plan = merge_rss(&local, backup.clone(), false);
+
+        // the local url of the feed 1 is kept
+        assert_eq!(plan.updated_rss.len(), 1);
+        assert_eq!(plan.updated_rss[0].uuid, "uuid-2");
+        assert_eq!(plan.updated_rss[0].name, "name-2-new");
+
+        assert_eq!(plan.added_rss.len(), 1);
+        assert_eq!(plan.added_rss[0].url, "https://example.com/feed-4");
+        assert_ne!(plan.added_rss[0].uuid, "uuid-3");
+        assert!(plan.removed_rss.is_empty());
+
+        let plan = merge_rss(&local, backup, true);
+        assert_eq!(plan.removed_rss.len(), 1);
+        assert_eq!(plan.removed_rss[0].uuid, "uuid-3");
+    }
+
+    #[test]
+    fn test_expired_auto_backups() {
+        let names = vec![
+            "rssbox-backup-20240101-120000-auto.json",
+            "rssbox-backup-20240103-120000-auto.json",
+            "rssbox-backup-20240102-120000.json",
+            "rssbox-backup-20240102-120000-auto.json",
+            "other.json",
+        ]
+        .into_iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            expired_auto_backups(names.clone(), 2),
+            vec!["rssbox-backup-20240101-120000-auto.json".to_string()]
+        );
+        assert_eq!(expired_auto_backups(names.clone(), 0).len(), 2);
+        assert!(expired_auto_backups(names, 5).is_empty());
+    }
+
+    #[test]
+    fn test_merge_favorites() {
+        let local = vec![RssEntry {
+            uuid: "uuid-1".to_string(),
+            url: "https://example.com/post-1".to_string(),
+            ..Default::default()
+        }];
+
+        let backup = vec![
+            RssEntry {
+                uuid: "uuid-2".to_string(),
+                url: "https://www.example.com/post-1/".to_string(),
+                ..Default::default()
+            },
+            RssEntry {
+                uuid: "uuid-3".to_string(),
+                url: "https://example.com/post-2".to_string(),
+                ..Default::default()
+            },
+            RssEntry {
+                uuid: "uuid-1".to_string(),
+                url: "https://example.com/post-3".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let items = merge_favorites(&local, backup);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].uuid, "uuid-3");
+        assert_ne!(items[1].uuid, "uuid-1");
+    }
+}
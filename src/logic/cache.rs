@@ -1,10 +1,19 @@
+use super::entry::FAVORITE_UUID;
 use super::message::{async_message_success, async_message_warn};
-use crate::slint_generatedAppWindow::{AppWindow, Logic, Store};
+use crate::slint_generatedAppWindow::{
+    AppWindow, DbStat as UIDbStat, DbTableStat as UIDbTableStat, Logic, SettingCache, Store,
+};
 use crate::{
-    db,
+    config,
+    db::{self, maintenance::DbStat, Repository},
     util::{self, translator::tr},
 };
-use slint::ComponentHandle;
+use anyhow::Result;
+use chrono::{Local, TimeZone, Utc};
+use slint::{ComponentHandle, Model, ModelRc, VecModel, Weak};
+use std::cmp::Reverse;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
 
 pub fn init(ui: &AppWindow) {
     init_cache(ui);
@@ -14,40 +23,308 @@ pub fn init(ui: &AppWindow) {
         ui_handle
             .unwrap()
             .global::<Store>()
-            .set_cache_size("0M".into());
+            .set_cache_size("0B".into());
 
         let ui = ui_handle.clone();
         tokio::spawn(async move {
-            match db::trash::delete_all().await {
+            match db::repo().delete_all_trash().await {
                 Err(e) => async_message_warn(
                     ui.clone(),
                     format!("{}. {}: {e:?}", tr("清除缓存失败"), tr("原因")),
                 ),
-                _ => async_message_success(ui.clone(), tr("清除缓存成功")),
+                _ => {
+                    async_message_success(ui.clone(), tr("清除缓存成功"));
+                    update_cache_info(ui).await;
+                }
             }
         });
     });
 
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>()
+        .on_remove_cache_older_than(move |days| {
+            let ui = ui_handle.clone();
+            tokio::spawn(async move {
+                let timestamp = Utc::now().timestamp() - i64::max(0, days as i64) * SECONDS_PER_DAY;
+                match db::repo().delete_trash_older_than(timestamp).await {
+                    Err(e) => async_message_warn(
+                        ui.clone(),
+                        format!("{}. {}: {e:?}", tr("清除缓存失败"), tr("原因")),
+                    ),
+                    Ok(count) => {
+                        async_message_success(
+                            ui.clone(),
+                            format!("{}. {}: {count}", tr("清除缓存成功"), tr("条目")),
+                        );
+                        update_cache_info(ui).await;
+                    }
+                }
+            });
+        });
+
     let ui_handle = ui.as_weak();
     ui.global::<Logic>().on_update_cache_size(move || {
         init_cache(&ui_handle.unwrap());
     });
+
+    ui.global::<Logic>().on_get_setting_cache(move || {
+        let config = config::cache();
+
+        SettingCache {
+            trash_expire_days: slint::format!("{}", config.trash_expire_days),
+            trash_max_rows: slint::format!("{}", config.trash_max_rows),
+            trash_recoverable_days: slint::format!("{}", config.trash_recoverable_days),
+            maintenance_interval_days: slint::format!("{}", config.maintenance_interval_days),
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_set_setting_cache(move |setting| {
+        let mut all = config::all();
+
+        all.cache.trash_expire_days = setting.trash_expire_days.parse().unwrap_or(90);
+        all.cache.trash_max_rows = setting.trash_max_rows.parse().unwrap_or(50000);
+        all.cache.trash_recoverable_days = setting.trash_recoverable_days.parse().unwrap_or(7);
+        all.cache.maintenance_interval_days =
+            setting.maintenance_interval_days.parse().unwrap_or(7);
+        _ = config::save(all);
+
+        init_cache(&ui_handle.unwrap());
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_update_db_stat(move || {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            update_db_stat(ui).await;
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_check_db_integrity(move || {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match db::maintenance::integrity_check(&db::pool()).await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("检查失败"), tr("原因")),
+                ),
+                Ok(problems) if problems.is_empty() => {
+                    async_message_success(ui.clone(), tr("数据库完整"))
+                }
+                Ok(problems) => async_message_warn(
+                    ui.clone(),
+                    format!("{}: {}", tr("数据库损坏"), problems.join("; ")),
+                ),
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_maintain_db(move || {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match maintain_db().await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("整理数据库失败"), tr("原因")),
+                ),
+                _ => {
+                    async_message_success(ui.clone(), tr("整理数据库成功"));
+                    update_cache_info(ui.clone()).await;
+                    update_db_stat(ui).await;
+                }
+            }
+        });
+    });
+}
+
+// Don't compact the database if it's broken, which may make things worse
+async fn maintain_db() -> Result<()> {
+    let problems = db::maintenance::integrity_check(&db::pool()).await?;
+    if !problems.is_empty() {
+        anyhow::bail!("{}: {}", tr("数据库损坏"), problems.join("; "));
+    }
+
+    db::maintenance::vacuum(&db::pool()).await?;
+    db::maintenance::analyze(&db::pool()).await?;
+    db::maintenance::checkpoint(&db::pool()).await?;
+
+    let mut all = config::all();
+    all.cache.last_maintenance_time = Utc::now().timestamp();
+    config::save(all)?;
+
+    Ok(())
+}
+
+fn is_maintenance_due() -> bool {
+    let config = config::cache();
+    config.maintenance_interval_days > 0
+        && config.last_maintenance_time + config.maintenance_interval_days * SECONDS_PER_DAY
+            <= Utc::now().timestamp()
+}
+
+// The entry tables are named by the rss uuid, show the rss name instead
+fn table_display_name(ui: &AppWindow, table_name: &str) -> String {
+    let Some(suuid) = table_name.strip_prefix("entry_") else {
+        return table_name.to_string();
+    };
+
+    if suuid == FAVORITE_UUID.replace('-', "_") {
+        return tr("收藏夹");
+    }
+
+    match ui
+        .global::<Store>()
+        .get_rss_lists()
+        .iter()
+        .find(|item| item.uuid.replace('-', "_") == suuid)
+    {
+        Some(rss) => rss.name.to_string(),
+        _ => table_name.to_string(),
+    }
+}
+
+fn to_ui_db_stat(ui: &AppWindow, stat: DbStat) -> UIDbStat {
+    let last_maintenance_time = config::cache().last_maintenance_time;
+    let last_maintenance = match Local.timestamp_opt(last_maintenance_time, 0).single() {
+        Some(time) if last_maintenance_time > 0 => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        _ => tr("从未"),
+    };
+
+    let mut tables = stat.tables;
+    tables.sort_by_key(|item| Reverse(item.bytes));
+
+    let tables = tables
+        .into_iter()
+        .map(|item| UIDbTableStat {
+            name: table_display_name(ui, &item.name).into(),
+            rows: slint::format!("{}", item.rows),
+            size: util::str::pretty_size_string(item.bytes as u64).into(),
+        })
+        .collect::<Vec<_>>();
+
+    UIDbStat {
+        total_size: util::str::pretty_size_string(stat.total_bytes as u64).into(),
+        free_size: util::str::pretty_size_string(stat.free_bytes as u64).into(),
+        last_maintenance: last_maintenance.into(),
+        tables: ModelRc::new(VecModel::from(tables)),
+    }
+}
+
+async fn update_db_stat(ui: Weak<AppWindow>) {
+    let stat = match db::maintenance::stat(&db::pool()).await {
+        Ok(stat) => stat,
+        Err(e) => {
+            log::warn!("Db stat error: {e:?}");
+            return;
+        }
+    };
+
+    let _ = slint::invoke_from_event_loop(move || {
+        let ui = ui.unwrap();
+        let stat = to_ui_db_stat(&ui, stat);
+        ui.global::<Store>().set_db_stat(stat);
+    });
+}
+
+// Remove the expired rows, and keep the table under the max row limitation.
+// `trash_expire_days <= 0` or `trash_max_rows <= 0` means never expire or no limitation.
+async fn expire_trash(repo: &dyn Repository, config: &config::Cache) -> Result<u64> {
+    let mut count = 0;
+
+    if config.trash_recoverable_days > 0 {
+        let timestamp = Utc::now().timestamp() - config.trash_recoverable_days * SECONDS_PER_DAY;
+        repo.forget_trash_older_than(timestamp).await?;
+    }
+
+    if config.trash_expire_days > 0 {
+        let timestamp = Utc::now().timestamp() - config.trash_expire_days * SECONDS_PER_DAY;
+        count += repo.delete_trash_older_than(timestamp).await?;
+    }
+
+    if config.trash_max_rows > 0 {
+        count += repo.shrink_trash(config.trash_max_rows).await?;
+    }
+
+    Ok(count)
+}
+
+async fn update_cache_info(ui: Weak<AppWindow>) {
+    let (count, size) = match (
+        db::repo().trash_row_count().await,
+        db::repo().trash_data_size().await,
+    ) {
+        (Ok(count), Ok(size)) => (count, size),
+        (Err(e), _) | (_, Err(e)) => {
+            log::warn!("Cache size error: {e:?}");
+            return;
+        }
+    };
+
+    let _ = slint::invoke_from_event_loop(move || {
+        let ui = ui.unwrap();
+        ui.global::<Store>()
+            .set_cache_size(util::str::pretty_size_string(size as u64).into());
+        ui.global::<Store>()
+            .set_cache_counts(slint::format!("{}", count));
+    });
 }
 
 fn init_cache(ui: &AppWindow) {
     let ui_handle = ui.as_weak();
     tokio::spawn(async move {
-        let ui = ui_handle.clone();
-        match db::trash::row_count().await {
-            Err(e) => log::warn!("Cache size error: {e:?}"),
-            Ok(count) => {
-                let _ = slint::invoke_from_event_loop(move || {
-                    ui.clone()
-                        .unwrap()
-                        .global::<Store>()
-                        .set_cache_size(util::str::pretty_size_string(count as u64 * 32).into());
-                });
+        match expire_trash(db::repo().as_ref(), &config::cache()).await {
+            Err(e) => log::warn!("Expire trash error: {e:?}"),
+            Ok(count) if count > 0 => log::info!("Expire trash rows: {count}"),
+            _ => (),
+        }
+
+        if is_maintenance_due() {
+            match maintain_db().await {
+                Err(e) => log::warn!("Maintain db error: {e:?}"),
+                _ => log::info!("Maintain db success"),
             }
         }
+
+        update_cache_info(ui_handle).await;
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{trash::TrashEntry, SqliteRepository, TrashRepo};
+
+    #[tokio::test]
+    async fn test_expire_trash() -> Result<()> {
+        let repo = SqliteRepository::memory().await?;
+        let now = Utc::now().timestamp();
+
+        for (md5, days) in [("md5-1", 0), ("md5-2", 3), ("md5-3", 10)] {
+            repo.restore_trash(&TrashEntry {
+                md5: md5.to_string(),
+                suuid: "suuid-1".to_string(),
+                data: "data".to_string(),
+                delete_time: now - days * SECONDS_PER_DAY,
+            })
+            .await?;
+        }
+
+        let config = config::Cache {
+            trash_expire_days: 7,
+            trash_max_rows: 2,
+            trash_recoverable_days: 1,
+            maintenance_interval_days: 0,
+            last_maintenance_time: 0,
+        };
+
+        assert_eq!(expire_trash(&repo, &config).await?, 1);
+        assert_eq!(repo.trash_row_count().await?, 2);
+
+        let items = repo.select_recoverable_trash().await?;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].md5, "md5-1");
+        Ok(())
+    }
+}
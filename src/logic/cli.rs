@@ -0,0 +1,230 @@
+use super::{opml, rss};
+use crate::{
+    config,
+    db::{self, entry::RssEntry, rss::RssConfig},
+    engine, util,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Feed {
+    pub uuid: String,
+    pub name: String,
+    pub url: String,
+    pub category: String,
+    pub unread_counts: usize,
+    pub total_counts: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Entry {
+    pub uuid: String,
+    pub feed_uuid: String,
+    pub feed_name: String,
+    pub title: String,
+    pub url: String,
+    pub pub_date: String,
+    pub author: String,
+    pub summary: String,
+    pub is_read: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SyncResult {
+    pub uuid: String,
+    pub name: String,
+    pub new_counts: usize,
+
+    // empty means success
+    pub error: String,
+}
+
+impl Entry {
+    // The favorite entrys may belong to the removed feeds
+    fn new(rss: Option<&RssConfig>, entry: RssEntry) -> Self {
+        Self {
+            feed_uuid: rss.map_or(entry.suuid, |rss| rss.uuid.clone()),
+            feed_name: rss.map(|rss| rss.name.clone()).unwrap_or_default(),
+            uuid: entry.uuid,
+            title: entry.title,
+            url: entry.url,
+            pub_date: entry.pub_date,
+            author: entry.author,
+            summary: entry.summary,
+            is_read: entry.is_read,
+        }
+    }
+}
+
+// Use the same database and config files as the app
+pub async fn init() {
+    config::init();
+    db::init(config::db_path().to_str().expect("invalid db path")).await;
+    super::init_engine();
+}
+
+// All the feeds if the id is empty
+async fn select_rss(id: Option<&str>) -> Result<Vec<RssConfig>> {
+    match id {
+        Some(id) => Ok(vec![engine::get().find_feed(id).await?]),
+        _ => engine::get().feeds().await,
+    }
+}
+
+fn new_feed(rss: RssConfig, entrys: &[RssEntry]) -> Feed {
+    Feed {
+        unread_counts: entrys.iter().filter(|item| !item.is_read).count(),
+        total_counts: entrys.len(),
+        uuid: rss.uuid,
+        name: rss.name,
+        url: rss.url,
+        category: rss.category,
+    }
+}
+
+pub async fn list() -> Result<Vec<Feed>> {
+    let mut items = vec![];
+    for rss in engine::get().feeds().await?.into_iter() {
+        let entrys = engine::get().entrys(&rss.uuid).await?;
+        items.push(new_feed(rss, &entrys));
+    }
+
+    Ok(items)
+}
+
+// The title of the channel is used if the name is empty
+pub async fn add(url: &str, name: &str, category: &str) -> Result<Feed> {
+    let mut config = RssConfig {
+        uuid: String::default(),
+        name: name.trim().to_string(),
+        url: url.trim().to_string(),
+        icon_index: 0,
+        use_http_proxy: false,
+        use_socks5_proxy: false,
+        is_favorite: false,
+        feed_format: "AUTO".to_string(),
+        category: category.trim().to_string(),
+        remote_id: String::default(),
+    };
+
+    if config.name.is_empty() {
+        config.name = rss::fetch_title(&config.clone().into())
+            .await
+            .context("fetch the title of the feed failed")?;
+    }
+
+    let rss = engine::get().add_feed(config).await?;
+    Ok(new_feed(rss, &[]))
+}
+
+pub async fn remove(id: &str) -> Result<Feed> {
+    let rss = engine::get().find_feed(id).await?;
+    let entrys = engine::get().entrys(&rss.uuid).await?;
+
+    let rss = engine::get().remove_feed(&rss.uuid).await?;
+    Ok(new_feed(rss, &entrys))
+}
+
+// Fetch the feeds and save the new entrys, the entrys which have been saved are skipped by the url
+pub async fn sync(id: Option<&str>) -> Result<Vec<SyncResult>> {
+    let mut results = vec![];
+
+    for rss in select_rss(id).await?.into_iter() {
+        let mut result = SyncResult {
+            uuid: rss.uuid.clone(),
+            name: rss.name,
+            new_counts: 0,
+            error: String::default(),
+        };
+
+        match engine::get().sync(&rss.uuid).await {
+            Ok(entrys) => result.new_counts = entrys.len(),
+            Err(e) => result.error = format!("{e:?}"),
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+pub async fn unread(id: Option<&str>) -> Result<Vec<Entry>> {
+    entrys(id, true).await
+}
+
+// The newest entrys are the first
+pub async fn entrys(id: Option<&str>, is_unread_only: bool) -> Result<Vec<Entry>> {
+    let mut items = vec![];
+    for rss in select_rss(id).await?.iter() {
+        let mut entrys = engine::get()
+            .entrys(&rss.uuid)
+            .await?
+            .into_iter()
+            .filter(|item| !is_unread_only || !item.is_read)
+            .map(|item| Entry::new(Some(rss), item))
+            .collect::<Vec<_>>();
+
+        entrys.reverse();
+        items.extend(entrys);
+    }
+
+    Ok(items)
+}
+
+pub async fn read(id: &str) -> Result<Entry> {
+    set_read(id, true).await
+}
+
+// The entry is found by the uuid or the prefix of the uuid
+pub async fn set_read(id: &str, is_read: bool) -> Result<Entry> {
+    let (rss, entry) = engine::get().find_entry(id).await?;
+    let entry = engine::get()
+        .set_read(&rss.uuid, &entry.uuid, is_read)
+        .await?;
+
+    Ok(Entry::new(Some(&rss), entry))
+}
+
+pub async fn favorites() -> Result<Vec<Entry>> {
+    let configs = engine::get().feeds().await?;
+
+    Ok(engine::get()
+        .favorites()
+        .await?
+        .into_iter()
+        .map(|entry| {
+            let rss = configs.iter().find(|rss| rss.uuid == entry.suuid);
+            Entry::new(rss, entry)
+        })
+        .collect())
+}
+
+pub async fn export_opml() -> Result<String> {
+    Ok(opml::export(&engine::get().feeds().await?))
+}
+
+// The feeds which have been subscribed are skipped
+pub async fn import_opml(text: &str) -> Result<usize> {
+    let mut configs = engine::get().feeds().await?;
+
+    let mut items = vec![];
+    for item in opml::parse(text)?.into_iter() {
+        let item = RssConfig::from(item);
+        if item.name.is_empty()
+            || configs
+                .iter()
+                .any(|rss| util::url::is_same(&rss.url, &item.url))
+        {
+            continue;
+        }
+
+        configs.push(item.clone());
+        items.push(item);
+    }
+
+    if items.is_empty() {
+        return Ok(0);
+    }
+
+    opml::_import_opml(items).await
+}
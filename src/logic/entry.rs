@@ -1,14 +1,15 @@
 use super::message::{async_message_success, async_message_warn};
 use crate::slint_generatedAppWindow::{AppWindow, Logic, RssEntry as UIRssEntry, Store};
 use crate::{
-    db::{self, entry::RssEntry},
-    message_info,
+    db::{self, entry::RssEntry, Repository},
+    engine, message_info,
     util::{crypto::md5_hex, translator::tr},
 };
 use anyhow::Result;
 use slint::{ComponentHandle, Model, SharedString, VecModel, Weak};
+use std::collections::BTreeMap;
 
-pub const FAVORITE_UUID: &str = "favorite-uuid";
+pub use crate::engine::FAVORITE_UUID;
 
 #[macro_export]
 macro_rules! store_rss_entrys {
@@ -33,7 +34,7 @@ macro_rules! store_favorite_entrys {
 }
 
 pub async fn get_from_db(suuid: &str) -> Vec<UIRssEntry> {
-    match db::entry::select_all(suuid).await {
+    match db::repo().select_all_entrys(suuid).await {
         Ok(items) => items
             .into_iter()
             .rev()
@@ -57,7 +58,7 @@ pub fn get_favorite_entrys(ui: &AppWindow) -> Vec<RssEntry> {
 
 pub fn init_favorite(ui: Weak<AppWindow>) {
     tokio::spawn(async move {
-        db::entry::new(FAVORITE_UUID).await.unwrap();
+        db::repo().new_entry_list(FAVORITE_UUID).await.unwrap();
         let entry_list = get_from_db(FAVORITE_UUID).await;
 
         let _ = slint::invoke_from_event_loop(move || {
@@ -83,7 +84,8 @@ pub fn init(ui: &AppWindow) {
             }
 
             store_rss_entrys!(ui).remove(index);
-            _remove_entry(ui.as_weak(), suuid, uuid, entry.url);
+            super::trash::show_undo(&ui, &suuid, vec![entry.clone().into()]);
+            _remove_entry(ui.as_weak(), suuid, entry.into());
             return;
         }
     });
@@ -98,13 +100,18 @@ pub fn init(ui: &AppWindow) {
 
         super::rss::reset_unread_counts(&ui, &suuid);
 
-        let urls = store_rss_entrys!(ui)
+        let entrys = store_rss_entrys!(ui)
             .iter()
-            .map(|item| item.url.clone())
-            .collect::<Vec<_>>();
+            .map(|item| item.into())
+            .collect::<Vec<RssEntry>>();
+
+        if entrys.is_empty() {
+            return;
+        }
 
         store_rss_entrys!(ui).set_vec(vec![]);
-        _remove_all_entrys(ui.as_weak(), suuid, urls);
+        super::trash::show_undo(&ui, &suuid, entrys.clone());
+        _remove_all_entrys(ui.as_weak(), suuid, entrys);
     });
 
     let ui_handle = ui.as_weak();
@@ -121,6 +128,7 @@ pub fn init(ui: &AppWindow) {
             }
 
             store_favorite_entrys!(ui).remove(index);
+            super::account::mirror_starred(&ui, &entry.suuid, vec![uuid.to_string()], false);
             break;
         }
 
@@ -130,6 +138,19 @@ pub fn init(ui: &AppWindow) {
     let ui_handle = ui.as_weak();
     ui.global::<Logic>().on_remove_all_favorite_entrys(move || {
         let ui = ui_handle.unwrap();
+
+        let mut starred: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for entry in ui.global::<Store>().get_rss_favorite_entrys().iter() {
+            starred
+                .entry(entry.suuid.into())
+                .or_default()
+                .push(entry.uuid.into());
+        }
+
+        for (suuid, uuids) in starred.into_iter() {
+            super::account::mirror_starred(&ui, &suuid, uuids, false);
+        }
+
         store_favorite_entrys!(ui).set_vec(vec![]);
         _remove_all_favorite_entrys(ui.as_weak());
     });
@@ -152,6 +173,7 @@ pub fn init(ui: &AppWindow) {
             }
 
             store_favorite_entrys!(ui).insert(0, entry.clone());
+            super::account::mirror_starred(&ui, &entry.suuid, vec![uuid.to_string()], true);
             _favorite_entry(ui.as_weak(), entry.into());
 
             return;
@@ -171,18 +193,34 @@ pub fn init(ui: &AppWindow) {
 
             entry.is_read = true;
             store_rss_entrys!(ui).set_row_data(index, entry.clone());
-            _set_entry_read(ui.as_weak(), suuid, entry.into());
+            _set_entry_read(ui.as_weak(), suuid, uuid);
 
             return;
         }
     });
 }
 
-fn _remove_entry(ui: Weak<AppWindow>, suuid: SharedString, uuid: SharedString, url: SharedString) {
-    tokio::spawn(async move {
-        _ = db::trash::insert(&md5_hex(&url)).await;
+pub(super) async fn trash_entry(
+    repo: &dyn Repository,
+    suuid: &str,
+    entry: &RssEntry,
+) -> Result<()> {
+    let data = serde_json::to_string(entry)?;
+    repo.insert_trash(&md5_hex(&entry.url), suuid, &data)
+        .await?;
+    Ok(())
+}
+
+fn _remove_entry(ui: Weak<AppWindow>, suuid: SharedString, entry: RssEntry) {
+    super::trash::run_in_order(async move {
+        if let Err(e) = trash_entry(db::repo().as_ref(), &suuid, &entry).await {
+            log::warn!("{e:?}");
+        }
 
-        match db::entry::delete(suuid.as_str(), uuid.as_str()).await {
+        match db::repo()
+            .delete_entry(suuid.as_str(), entry.uuid.as_str())
+            .await
+        {
             Err(e) => async_message_warn(
                 ui.clone(),
                 format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
@@ -192,20 +230,22 @@ fn _remove_entry(ui: Weak<AppWindow>, suuid: SharedString, uuid: SharedString, u
     });
 }
 
-fn _remove_all_entrys(ui: Weak<AppWindow>, suuid: SharedString, urls: Vec<SharedString>) {
-    tokio::spawn(async move {
-        for url in urls.into_iter() {
-            _ = db::trash::insert(&md5_hex(&url)).await;
+fn _remove_all_entrys(ui: Weak<AppWindow>, suuid: SharedString, entrys: Vec<RssEntry>) {
+    super::trash::run_in_order(async move {
+        for entry in entrys.iter() {
+            if let Err(e) = trash_entry(db::repo().as_ref(), &suuid, entry).await {
+                log::warn!("{e:?}");
+            }
         }
 
-        _ = db::entry::delete_all(suuid.as_str()).await;
+        _ = db::repo().delete_all_entrys(suuid.as_str()).await;
         async_message_success(ui.clone(), tr("删除成功"));
     });
 }
 
 fn _remove_favorite_entry(ui: Weak<AppWindow>, uuid: SharedString) {
     tokio::spawn(async move {
-        match db::entry::delete(FAVORITE_UUID, uuid.as_str()).await {
+        match db::repo().delete_entry(FAVORITE_UUID, uuid.as_str()).await {
             Err(e) => async_message_warn(
                 ui.clone(),
                 format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
@@ -217,7 +257,7 @@ fn _remove_favorite_entry(ui: Weak<AppWindow>, uuid: SharedString) {
 
 fn _remove_all_favorite_entrys(ui: Weak<AppWindow>) {
     tokio::spawn(async move {
-        match db::entry::delete_all(FAVORITE_UUID).await {
+        match db::repo().delete_all_entrys(FAVORITE_UUID).await {
             Err(e) => async_message_warn(
                 ui.clone(),
                 format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
@@ -227,15 +267,17 @@ fn _remove_all_favorite_entrys(ui: Weak<AppWindow>) {
     });
 }
 
-async fn _inner_favorite_entry(entry: RssEntry) -> Result<()> {
+pub async fn insert_favorite_entry(entry: RssEntry) -> Result<()> {
     let data = serde_json::to_string(&entry)?;
-    db::entry::insert(FAVORITE_UUID, entry.uuid.as_str(), &data).await?;
+    db::repo()
+        .insert_entry(FAVORITE_UUID, entry.uuid.as_str(), &data)
+        .await?;
     Ok(())
 }
 
 fn _favorite_entry(ui: Weak<AppWindow>, entry: RssEntry) {
     tokio::spawn(async move {
-        match _inner_favorite_entry(entry).await {
+        match engine::get().add_favorite(entry).await {
             Err(e) => async_message_warn(
                 ui.clone(),
                 format!("{}. {}: {e:?}", tr("收藏失败"), tr("原因")),
@@ -245,27 +287,19 @@ fn _favorite_entry(ui: Weak<AppWindow>, entry: RssEntry) {
     });
 }
 
-async fn _inner_set_entry_read(suuid: &str, entry: RssEntry) -> Result<()> {
-    let data = serde_json::to_string(&entry)?;
-    db::entry::update(suuid, entry.uuid.as_str(), &data).await?;
-    Ok(())
-}
-
-fn _set_entry_read(ui: Weak<AppWindow>, suuid: SharedString, entry: RssEntry) {
+// The read state is mirrored to the server by the engine
+fn _set_entry_read(ui: Weak<AppWindow>, suuid: SharedString, uuid: SharedString) {
     tokio::spawn(async move {
-        match _inner_set_entry_read(suuid.as_str(), entry).await {
-            Err(e) => async_message_warn(ui, format!("{}. {}: {e:?}", tr("保存失败"), tr("原因"))),
-            _ => (),
+        if let Err(e) = engine::get()
+            .set_read(suuid.as_str(), uuid.as_str(), true)
+            .await
+        {
+            async_message_warn(ui, format!("{}. {}: {e:?}", tr("保存失败"), tr("原因")));
         }
     });
 }
 
-async fn update_new_entry(suuid: &str, entry: RssEntry) -> Result<()> {
-    let data = serde_json::to_string(&entry)?;
-    db::entry::insert(suuid, entry.uuid.as_str(), &data).await?;
-    Ok(())
-}
-
+// Show the new entrys which have been saved by the engine
 pub fn update_new_entrys(ui: &AppWindow, suuid: &str, entrys: Vec<RssEntry>) {
     for (index, mut rss) in ui.global::<Store>().get_rss_lists().iter().enumerate() {
         if rss.uuid != suuid {
@@ -280,23 +314,14 @@ pub fn update_new_entrys(ui: &AppWindow, suuid: &str, entrys: Vec<RssEntry>) {
         }
 
         rss.is_update_failed = false;
-        rss.unread_counts += unfound_list.len() as i32;
-
-        for mut item in unfound_list.into_iter() {
-            item.suuid = suuid.into();
+        rss.unread_counts += unfound_list.iter().filter(|item| !item.is_read).count() as i32;
 
+        for item in unfound_list.into_iter() {
             rss.entry
                 .as_any()
                 .downcast_ref::<VecModel<UIRssEntry>>()
                 .expect("We know we set a VecModel earlier")
-                .insert(0, item.clone().into());
-
-            let suuid = suuid.to_string();
-            tokio::spawn(async move {
-                if let Err(e) = update_new_entry(&suuid, item).await {
-                    log::warn!("{e:?}");
-                }
-            });
+                .insert(0, item.into());
         }
 
         ui.global::<Store>()
@@ -306,3 +331,17 @@ pub fn update_new_entrys(ui: &AppWindow, suuid: &str, entrys: Vec<RssEntry>) {
         return;
     }
 }
+
+// Show the favorite entry which has been saved in the database
+pub fn push_favorite_entry(ui: &AppWindow, entry: RssEntry) {
+    if ui
+        .global::<Store>()
+        .get_rss_favorite_entrys()
+        .iter()
+        .any(|item| item.uuid == entry.uuid.as_str())
+    {
+        return;
+    }
+
+    store_favorite_entrys!(ui).insert(0, entry.into());
+}
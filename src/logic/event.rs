@@ -0,0 +1,59 @@
+use super::{entry, find, rss, setting};
+use crate::engine::{self, Event};
+use crate::slint_generatedAppWindow::{AppWindow, Store};
+use slint::ComponentHandle;
+use tokio::sync::broadcast::error::RecvError;
+
+// Apply the changes of the engine to the UI. The changes may come from the UI itself,
+// so every handler does nothing if the UI has been updated.
+pub fn init(ui: &AppWindow) {
+    let mut events = engine::get().subscribe();
+
+    let ui = ui.as_weak();
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let ui = ui.clone();
+                    let _ = slint::invoke_from_event_loop(move || {
+                        if let Some(ui) = ui.upgrade() {
+                            apply(&ui, event);
+                        }
+                    });
+                }
+                Err(RecvError::Lagged(counts)) => log::warn!("lost {counts} events"),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+fn apply(ui: &AppWindow, event: Event) {
+    match event {
+        Event::FeedAdded(item) => {
+            if rss::get_rss_config(ui, &item.uuid).is_none() {
+                rss::push_rss(ui, item);
+            }
+        }
+        Event::FeedUpdated(item) => rss::update_rss(ui, item),
+        Event::FeedRemoved(suuid) => rss::pop_rss(ui, &suuid),
+        Event::Synced { suuid, entrys } => {
+            entry::update_new_entrys(ui, &suuid, entrys);
+
+            if suuid == ui.global::<Store>().get_current_rss_uuid().as_str() {
+                rss::notify_ui_update_unread_counts(ui);
+                ui.global::<Store>().invoke_refresh_current_tab();
+            }
+        }
+        Event::EntryRead {
+            suuid,
+            uuid,
+            is_read,
+        } => rss::update_entry_read(ui, &suuid, &uuid, is_read),
+        Event::FavoriteAdded(item) => entry::push_favorite_entry(ui, item),
+        Event::Blacklist { url, is_blacklist } => {
+            find::update_find_entry(ui, &url, |item| item.is_blacklist = is_blacklist)
+        }
+        Event::SettingsChanged(_) => setting::init_setting(ui),
+    }
+}
@@ -1,42 +1,141 @@
 use super::message::async_message_warn;
-use crate::slint_generatedAppWindow::{AppWindow, FindEntry as UIFindEntry, Logic, Store};
+use crate::slint_generatedAppWindow::{
+    AppWindow, FindBundle as UIFindBundle, FindEntry as UIFindEntry,
+    FindRecommend as UIFindRecommend, Logic, Store,
+};
 use crate::{
     config,
-    db::{self, ComEntry},
-    message_info, message_success,
-    util::{crypto::md5_hex, http, translator::tr},
+    db::{self, rss::RssConfig, ComEntry},
+    engine, message_info, message_success,
+    util::{
+        http,
+        recommend::{self, Candidate, Profile},
+        search,
+        translator::tr,
+        url,
+    },
 };
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use slint::{ComponentHandle, Model, SharedString, VecModel, Weak};
-use std::{cmp::Ordering, time::Duration};
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    time::Duration,
+};
 
 const FIND_UUID: &str = "find-uuid";
-const RSS_ENTRY_URL_CN: &str = "https://heng30.xyz/apisvr/rssbox/rss/list/cn";
-const RSS_ENTRY_URL_EN: &str = "https://heng30.xyz/apisvr/rssbox/rss/list/en";
 const RSS_VALID_CN: &str = include_str!("../../data/rss-valid-cn.json");
 const RSS_VALID_EN: &str = include_str!("../../data/rss-valid-en.json");
+const RSS_BUNDLES_CN: &str = include_str!("../../data/rss-bundles-cn.json");
+const RSS_BUNDLES_EN: &str = include_str!("../../data/rss-bundles-en.json");
 
-#[derive(Serialize, Deserialize, Hash, Debug, Clone)]
+const CATEGORY_SEPARATOR: char = '/';
+const MAX_RECOMMENDS: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct FindEntry {
     pub name: String,
     pub url: String,
+
+    // the names of the OPML folders, joined by `/`
+    #[serde(default)]
+    pub category: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    // `cn` or `en`
+    #[serde(default)]
+    pub language: String,
+
+    #[serde(default)]
+    pub site_url: String,
+
+    // `%Y-%m-%d`, the date when the feed was fetched successfully
+    #[serde(default)]
+    pub last_verified: String,
+}
+
+// The curated feeds which can be subscribed at once
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FindBundle {
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    pub items: Vec<FindEntry>,
 }
 
+impl From<FindEntry> for RssConfig {
+    fn from(entry: FindEntry) -> Self {
+        RssConfig {
+            uuid: String::default(),
+            name: entry.name,
+            url: entry.url,
+            icon_index: 0,
+            use_http_proxy: false,
+            use_socks5_proxy: false,
+            is_favorite: false,
+            feed_format: "AUTO".to_string(),
+            category: entry.category,
+            remote_id: String::default(),
+        }
+    }
+}
+
+// The entrys are the same feed if their normalized urls are the same
 impl PartialEq for FindEntry {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name || self.url == other.url
+        url::is_same(&self.url, &other.url)
     }
 }
 
 impl Eq for FindEntry {}
 
+impl Hash for FindEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        url::normalize(&self.url).hash(state);
+    }
+}
+
 impl From<FindEntry> for UIFindEntry {
     fn from(entry: FindEntry) -> Self {
         UIFindEntry {
             name: entry.name.into(),
             url: entry.url.into(),
+            category: entry.category.into(),
+            description: entry.description.into(),
+            language: entry.language.into(),
+            site_url: entry.site_url.into(),
+            last_verified: entry.last_verified.into(),
             is_blacklist: false,
+            is_checked: false,
+        }
+    }
+}
+
+impl From<UIFindEntry> for FindEntry {
+    fn from(entry: UIFindEntry) -> Self {
+        FindEntry {
+            name: entry.name.into(),
+            url: entry.url.into(),
+            category: entry.category.into(),
+            description: entry.description.into(),
+            language: entry.language.into(),
+            site_url: entry.site_url.into(),
+            last_verified: entry.last_verified.into(),
+        }
+    }
+}
+
+impl From<FindBundle> for UIFindBundle {
+    fn from(bundle: FindBundle) -> Self {
+        UIFindBundle {
+            name: bundle.name.into(),
+            description: bundle.description.into(),
+            counts: bundle.items.len() as i32,
         }
     }
 }
@@ -52,6 +151,50 @@ macro_rules! store_find_entrys {
     };
 }
 
+#[macro_export]
+macro_rules! store_find_entrys_category {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_find_entrys_category()
+            .as_any()
+            .downcast_ref::<VecModel<UIFindEntry>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+#[macro_export]
+macro_rules! store_find_categorys {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_find_categorys()
+            .as_any()
+            .downcast_ref::<VecModel<SharedString>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+#[macro_export]
+macro_rules! store_find_bundles {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_find_bundles()
+            .as_any()
+            .downcast_ref::<VecModel<UIFindBundle>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+#[macro_export]
+macro_rules! store_find_recommends {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_find_recommends()
+            .as_any()
+            .downcast_ref::<VecModel<UIFindRecommend>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
 #[macro_export]
 macro_rules! store_find_entrys_keyword {
     ($ui:expr) => {
@@ -73,10 +216,7 @@ fn sort_fn(a: &UIFindEntry, b: &UIFindEntry) -> Ordering {
 
 async fn sort_list(items: &mut Vec<UIFindEntry>) {
     for item in items.iter_mut() {
-        if db::blacklist::is_exist(&md5_hex(item.url.as_str()))
-            .await
-            .is_ok()
-        {
+        if engine::get().is_blacklist(item.url.as_str()).await {
             item.is_blacklist = true;
         }
     }
@@ -84,17 +224,212 @@ async fn sort_list(items: &mut Vec<UIFindEntry>) {
     items.sort_by(sort_fn);
 }
 
+// The top level categories, the nested categories are browsed with their parents
+fn top_category(category: &str) -> &str {
+    category
+        .split(CATEGORY_SEPARATOR)
+        .next()
+        .unwrap_or_default()
+        .trim()
+}
+
+fn find_categorys<'a>(categorys: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut items = categorys
+        .map(top_category)
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>();
+
+    items.sort();
+    items.dedup();
+    items
+}
+
+// Show the entrys of the category and the categorys in the catalog
+fn set_find_entrys(ui: &AppWindow, items: Vec<UIFindEntry>) {
+    let categorys = find_categorys(items.iter().map(|item| item.category.as_str()));
+
+    store_find_categorys!(ui).set_vec(
+        categorys
+            .into_iter()
+            .map(|item| item.into())
+            .collect::<Vec<SharedString>>(),
+    );
+    store_find_entrys!(ui).set_vec(items);
+
+    let category = ui.global::<Store>().get_find_category();
+    update_find_category(ui, category);
+    update_find_recommends(ui);
+}
+
+// Recommend the catalog feeds which are similar to the subscribed feeds and the favorite entrys
+fn update_find_recommends(ui: &AppWindow) {
+    let mut profiles = ui
+        .global::<Store>()
+        .get_rss_lists()
+        .iter()
+        .map(|rss| {
+            let mut profile = Profile::new(&rss.name, &rss.url, &rss.category, rss.is_favorite);
+            for entry in rss.entry.iter() {
+                profile.add_text(&format!("{} {}", entry.title, entry.tags), false);
+            }
+            (rss.uuid, profile)
+        })
+        .collect::<Vec<_>>();
+
+    for entry in ui.global::<Store>().get_rss_favorite_entrys().iter() {
+        if let Some((_, profile)) = profiles.iter_mut().find(|(uuid, _)| *uuid == entry.suuid) {
+            profile.add_text(&format!("{} {}", entry.title, entry.tags), true);
+        }
+    }
+
+    let profiles = profiles
+        .into_iter()
+        .map(|(_, profile)| profile)
+        .collect::<Vec<_>>();
+
+    let entrys = ui
+        .global::<Store>()
+        .get_find_entrys()
+        .iter()
+        .filter(|item| !item.is_blacklist && !super::rss::is_exist_url(ui, &item.url))
+        .collect::<Vec<_>>();
+
+    let ui = ui.as_weak();
+    tokio::spawn(async move {
+        let candidates = entrys
+            .iter()
+            .map(|item| Candidate {
+                name: item.name.to_string(),
+                url: item.url.to_string(),
+                category: item.category.to_string(),
+                description: item.description.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        let items = recommend::recommend(&profiles, &candidates, MAX_RECOMMENDS)
+            .into_iter()
+            .map(|item| UIFindRecommend {
+                entry: entrys[item.index].clone(),
+                reason: format!("{} {}", tr("因为你订阅了"), profiles[item.profile].name).into(),
+            })
+            .collect::<Vec<_>>();
+
+        let _ = slint::invoke_from_event_loop(move || {
+            store_find_recommends!(ui.unwrap()).set_vec(items);
+        });
+    });
+}
+
+fn update_find_category(ui: &AppWindow, category: SharedString) {
+    let items = if category.is_empty() {
+        vec![]
+    } else {
+        ui.global::<Store>()
+            .get_find_entrys()
+            .iter()
+            .filter(|item| top_category(&item.category) == category.as_str())
+            .collect()
+    };
+
+    if items.is_empty() {
+        ui.global::<Store>().set_find_category("".into());
+    } else {
+        ui.global::<Store>().set_find_category(category);
+    }
+
+    store_find_entrys_category!(ui).set_vec(items);
+}
+
+// The entry is in all the lists, so the index of a list can't be used
+pub fn update_find_entry(ui: &AppWindow, url: &str, update: impl Fn(&mut UIFindEntry)) {
+    for model in [
+        ui.global::<Store>().get_find_entrys(),
+        ui.global::<Store>().get_find_entrys_keyword(),
+        ui.global::<Store>().get_find_entrys_category(),
+    ] {
+        for (index, mut item) in model.iter().enumerate() {
+            if item.url == url {
+                update(&mut item);
+                model.set_row_data(index, item);
+            }
+        }
+    }
+}
+
+fn remove_find_entrys(ui: &AppWindow, urls: &[SharedString]) {
+    let keyword_items = ui
+        .global::<Store>()
+        .get_find_entrys_keyword()
+        .iter()
+        .filter(|item| !urls.contains(&item.url))
+        .collect::<Vec<_>>();
+    store_find_entrys_keyword!(ui).set_vec(keyword_items);
+
+    let items = ui
+        .global::<Store>()
+        .get_find_entrys()
+        .iter()
+        .filter(|item| !urls.contains(&item.url))
+        .collect::<Vec<_>>();
+    set_find_entrys(ui, items);
+}
+
+pub fn rss_bundles(text: &str) -> Result<Vec<FindBundle>> {
+    Ok(serde_json::from_str::<Vec<_>>(text)?)
+}
+
+fn current_rss_bundles() -> Vec<FindBundle> {
+    let text = if config::ui().language == "cn" {
+        RSS_BUNDLES_CN
+    } else {
+        RSS_BUNDLES_EN
+    };
+
+    match rss_bundles(text) {
+        Ok(items) => items,
+        Err(e) => {
+            log::warn!("{e:?}");
+            vec![]
+        }
+    }
+}
+
+// Only the entrys which have been subscribed are removed from the find list
+fn subscribe_find_entrys(ui: &AppWindow, items: Vec<FindEntry>) {
+    super::rss::new_rss_list(
+        ui,
+        items.into_iter().map(|item| item.into()).collect(),
+        |ui, added| {
+            let urls = added
+                .iter()
+                .map(|rss| rss.url.clone().into())
+                .collect::<Vec<SharedString>>();
+            remove_find_entrys(ui, &urls);
+        },
+    );
+}
+
 fn init_find(ui: &AppWindow) {
     store_find_entrys!(ui).set_vec(vec![]);
     store_find_entrys_keyword!(ui).set_vec(vec![]);
+    store_find_entrys_category!(ui).set_vec(vec![]);
+    store_find_categorys!(ui).set_vec(vec![]);
+    ui.global::<Store>().set_find_category("".into());
+    store_find_bundles!(ui).set_vec(
+        current_rss_bundles()
+            .into_iter()
+            .map(|item| item.into())
+            .collect::<Vec<UIFindBundle>>(),
+    );
 
     let rss_configs = super::rss::get_rss_configs(&ui);
 
     let ui = ui.as_weak();
     tokio::spawn(async move {
-        _ = db::entry::new(FIND_UUID).await;
+        _ = db::repo().new_entry_list(FIND_UUID).await;
 
-        match db::entry::select_all(FIND_UUID).await {
+        match db::repo().select_all_entrys(FIND_UUID).await {
             Err(e) => log::warn!("{e:?}"),
             Ok(items) => {
                 let items = items
@@ -118,7 +453,7 @@ fn init_find(ui: &AppWindow) {
 
                 let ui = ui.clone();
                 let _ = slint::invoke_from_event_loop(move || {
-                    store_find_entrys!(ui.unwrap()).set_vec(unadd_list);
+                    set_find_entrys(&ui.unwrap(), unadd_list);
                 });
             }
         }
@@ -141,13 +476,21 @@ pub fn init(ui: &AppWindow) {
     ui.global::<Logic>()
         .on_update_find_entrylist(move |keyword| {
             let ui = ui_handle.unwrap();
-            let mut keyword_list = vec![];
-
-            for item in ui.global::<Store>().get_find_entrys().iter() {
-                if item.name.contains(keyword.as_str()) {
-                    keyword_list.push(item);
-                }
-            }
+            let entrys = ui
+                .global::<Store>()
+                .get_find_entrys()
+                .iter()
+                .collect::<Vec<_>>();
+
+            let keyword_list = search::search(
+                keyword.as_str(),
+                entrys
+                    .iter()
+                    .map(|item| (item.name.as_str(), item.url.as_str())),
+            )
+            .into_iter()
+            .map(|index| entrys[index].clone())
+            .collect::<Vec<_>>();
 
             store_find_entrys_keyword!(ui).set_vec(keyword_list);
             message_success!(ui, tr("查找完成"));
@@ -155,38 +498,92 @@ pub fn init(ui: &AppWindow) {
 
     let ui_handle = ui.as_weak();
     ui.global::<Logic>()
-        .on_recover_from_find_blacklist(move |index, url| {
+        .on_recover_from_find_blacklist(move |url| {
+            let ui = ui_handle.unwrap();
+            update_find_entry(&ui, &url, |item| item.is_blacklist = false);
+            message_success!(ui, tr("成功移除黑名单"));
+
+            _recover_from_find_blacklist(url);
+        });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_update_find_recommends(move || {
+        update_find_recommends(&ui_handle.unwrap());
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>()
+        .on_subscribe_find_recommend(move |index| {
             let ui = ui_handle.unwrap();
-            let mut item = ui
+            let Some(item) = ui
                 .global::<Store>()
-                .get_find_entrys()
+                .get_find_recommends()
                 .row_data(index as usize)
-                .expect("we know find etnrys index is valid");
-
-            item.is_blacklist = false;
-            ui.global::<Store>()
-                .get_find_entrys()
-                .set_row_data(index as usize, item);
+            else {
+                return;
+            };
 
-            message_success!(ui, tr("成功移除黑名单"));
+            store_find_recommends!(ui).remove(index as usize);
+            subscribe_find_entrys(&ui, vec![item.entry.into()]);
+        });
 
-            _recover_from_find_blacklist(url);
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_toggle_find_entry(move |url| {
+        update_find_entry(&ui_handle.unwrap(), &url, |item| {
+            item.is_checked = !item.is_checked
         });
+    });
 
     let ui_handle = ui.as_weak();
-    ui.global::<Logic>().on_add_to_find_blacklist(move |url| {
+    ui.global::<Logic>().on_clear_find_checked(move || {
         let ui = ui_handle.unwrap();
-
-        for (index, mut item) in ui.global::<Store>().get_find_entrys().iter().enumerate() {
-            if item.url == url {
-                item.is_blacklist = true;
-                ui.global::<Store>()
-                    .get_find_entrys()
-                    .set_row_data(index, item);
-                break;
+        for item in ui.global::<Store>().get_find_entrys().iter() {
+            if item.is_checked {
+                update_find_entry(&ui, &item.url, |item| item.is_checked = false);
             }
         }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_subscribe_find_entrys(move || {
+        let ui = ui_handle.unwrap();
+        let items = ui
+            .global::<Store>()
+            .get_find_entrys()
+            .iter()
+            .filter(|item| item.is_checked)
+            .map(|item| item.into())
+            .collect::<Vec<FindEntry>>();
+
+        if items.is_empty() {
+            message_info!(ui, tr("请选择条目"));
+            return;
+        }
+
+        ui.global::<Store>().set_find_is_selecting(false);
+        subscribe_find_entrys(&ui, items);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_subscribe_find_bundle(move |index| {
+        let ui = ui_handle.unwrap();
+        let Some(bundle) = current_rss_bundles().into_iter().nth(index as usize) else {
+            return;
+        };
+
+        subscribe_find_entrys(&ui, bundle.items);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>()
+        .on_update_find_category(move |category| {
+            update_find_category(&ui_handle.unwrap(), category);
+        });
 
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_add_to_find_blacklist(move |url| {
+        let ui = ui_handle.unwrap();
+        update_find_entry(&ui, &url, |item| item.is_blacklist = true);
         _add_to_find_blacklist(url);
     });
 
@@ -201,14 +598,14 @@ pub fn init(ui: &AppWindow) {
 }
 
 async fn _inner_fetch_all_find_entrys() -> Result<Vec<FindEntry>> {
-    let url = if config::ui().language == "cn" {
-        RSS_ENTRY_URL_CN
+    let url = config::endpoint().rss_list_url(if config::ui().language == "cn" {
+        "cn"
     } else {
-        RSS_ENTRY_URL_EN
-    };
+        "en"
+    });
 
     Ok(http::client(None)?
-        .get(url)
+        .get(&url)
         .timeout(Duration::from_secs(30))
         .send()
         .await?
@@ -249,10 +646,12 @@ fn _fetch_all_find_entrys(ui: Weak<AppWindow>) {
             }
         }
 
-        _ = db::entry::delete_all(FIND_UUID).await;
+        _ = db::repo().delete_all_entrys(FIND_UUID).await;
         for (index, item) in items.iter().enumerate() {
             if let Ok(data) = serde_json::to_string(item) {
-                _ = db::entry::insert(FIND_UUID, &format!("{}", index), &data).await;
+                _ = db::repo()
+                    .insert_entry(FIND_UUID, &format!("{}", index), &data)
+                    .await;
             }
         }
 
@@ -266,7 +665,7 @@ fn _fetch_all_find_entrys(ui: Weak<AppWindow>) {
         let ui = ui.clone();
         let _ = slint::invoke_from_event_loop(move || {
             let ui = ui.unwrap();
-            store_find_entrys!(ui).set_vec(ui_items);
+            set_find_entrys(&ui, ui_items);
             message_success!(ui, tr("刷新成功"));
             ui.global::<Store>()
                 .set_find_entrys_counts_flag(!ui.global::<Store>().get_find_entrys_counts_flag());
@@ -276,12 +675,74 @@ fn _fetch_all_find_entrys(ui: Weak<AppWindow>) {
 
 fn _recover_from_find_blacklist(url: SharedString) {
     tokio::spawn(async move {
-        _ = db::blacklist::delete(&md5_hex(url.as_str())).await;
+        _ = engine::get().set_blacklist(url.as_str(), false).await;
     });
 }
 
 fn _add_to_find_blacklist(url: SharedString) {
     tokio::spawn(async move {
-        _ = db::blacklist::insert(&md5_hex(url.as_str())).await;
+        _ = engine::get().set_blacklist(url.as_str(), true).await;
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rss_valid() -> Result<()> {
+        let items = rss_valid(
+            r#"[
+            { "name": "name-1", "url": "https://example.com/feed" },
+            { "name": "name-2", "url": "https://example.com/rss", "category": "Tech/Rust",
+              "description": "description-2", "language": "en", "site_url": "https://example.com",
+              "last_verified": "2024-01-01" }
+        ]"#,
+        )?;
+
+        assert_eq!(items.len(), 2);
+        assert!(items[0].category.is_empty());
+        assert_eq!(items[1].category, "Tech/Rust");
+        assert_eq!(items[1].last_verified, "2024-01-01");
+        Ok(())
+    }
+
+    #[test]
+    fn test_rss_bundles() -> Result<()> {
+        for text in [RSS_BUNDLES_CN, RSS_BUNDLES_EN] {
+            let bundles = rss_bundles(text)?;
+            assert!(!bundles.is_empty());
+            assert!(bundles.iter().all(|item| !item.items.is_empty()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_entry_eq() {
+        let entry = |name: &str, url: &str| FindEntry {
+            name: name.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        };
+
+        let items = [
+            entry("name-1", "https://example.com/feed/"),
+            entry("name-2", "http://www.example.com/feed"),
+            entry("name-1", "https://example.com/rss"),
+        ]
+        .into_iter()
+        .collect::<std::collections::HashSet<_>>();
+
+        assert_eq!(items.len(), 2);
+        assert_ne!(
+            entry("name-1", "https://a.com"),
+            entry("name-1", "https://b.com")
+        );
+    }
+
+    #[test]
+    fn test_find_categorys() {
+        let categorys = find_categorys(["Tech/Rust", "", "News", "Tech", " Tech /Go"].into_iter());
+        assert_eq!(categorys, vec!["News", "Tech"]);
+    }
+}
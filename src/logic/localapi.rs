@@ -0,0 +1,233 @@
+use super::cli;
+use crate::{config, engine};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        header::{HeaderName, AUTHORIZATION, CONTENT_TYPE},
+        HeaderMap, StatusCode,
+    },
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::{net::SocketAddr, sync::Arc};
+use uuid::Uuid;
+
+pub const OPENAPI: &str = include_str!("../../data/localapi-openapi.json");
+
+struct AppState {
+    token: String,
+}
+
+type AppError = (StatusCode, String);
+
+#[derive(Deserialize, Debug)]
+struct NewFeed {
+    url: String,
+
+    #[serde(default)]
+    name: String,
+
+    #[serde(default)]
+    category: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct EntrysQuery {
+    feed: Option<String>,
+
+    #[serde(default)]
+    unread: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct SyncQuery {
+    feed: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReadState {
+    is_read: bool,
+}
+
+fn internal_error(e: impl std::fmt::Display) -> AppError {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn bad_request(e: impl std::fmt::Display) -> AppError {
+    (StatusCode::BAD_REQUEST, e.to_string())
+}
+
+fn not_found(e: impl std::fmt::Display) -> AppError {
+    (StatusCode::NOT_FOUND, e.to_string())
+}
+
+// The api is off by default, and a token is generated when it's turned on for the first time.
+// The changes are shown in the UI by the events of the engine.
+pub fn init() {
+    let mut conf = config::local_api();
+    if !conf.enabled {
+        return;
+    }
+
+    if conf.token.is_empty() {
+        conf.token = Uuid::new_v4().to_string();
+
+        let mut all = config::all();
+        all.local_api = conf.clone();
+        if let Err(e) = config::save(all) {
+            log::warn!("{e:?}");
+        }
+    }
+
+    let state = Arc::new(AppState { token: conf.token });
+
+    tokio::spawn(async move {
+        if let Err(e) = serve(state, conf.port).await {
+            log::warn!("{e:?}");
+        }
+    });
+}
+
+async fn serve(state: Arc<AppState>, port: u16) -> Result<()> {
+    let app = Router::new()
+        .route("/api/v1/openapi.json", get(openapi))
+        .route("/api/v1/feeds", get(list_feeds).post(add_feed))
+        .route("/api/v1/feeds/:id", delete(remove_feed))
+        .route("/api/v1/entries", get(list_entrys))
+        .route("/api/v1/entries/:id/read", put(set_read))
+        .route("/api/v1/favorites", get(list_favorites))
+        .route("/api/v1/sync", post(sync))
+        .with_state(state);
+
+    // Never listen on the other interfaces, the api is only for the local tools
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    log::info!("local api listen on {addr}");
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("bind {addr} failed"))?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
+
+fn check_token(state: &AppState, headers: &HeaderMap) -> Result<(), AppError> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim())
+        .unwrap_or_default();
+
+    if token.is_empty() || token != state.token {
+        return Err((StatusCode::UNAUTHORIZED, "invalid api token".to_string()));
+    }
+
+    Ok(())
+}
+
+// The sync takes a while, so it runs in the background
+fn spawn_sync(suuids: Vec<String>) {
+    tokio::spawn(async move {
+        for suuid in suuids.iter() {
+            if let Err(e) = engine::get().sync(suuid).await {
+                log::warn!("{e:?}");
+            }
+        }
+    });
+}
+
+async fn openapi() -> ([(HeaderName, &'static str); 1], &'static str) {
+    ([(CONTENT_TYPE, "application/json")], OPENAPI)
+}
+
+async fn list_feeds(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<cli::Feed>>, AppError> {
+    check_token(&state, &headers)?;
+    Ok(Json(cli::list().await.map_err(internal_error)?))
+}
+
+async fn add_feed(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(data): Json<NewFeed>,
+) -> Result<(StatusCode, Json<cli::Feed>), AppError> {
+    check_token(&state, &headers)?;
+
+    let feed = cli::add(&data.url, &data.name, &data.category)
+        .await
+        .map_err(bad_request)?;
+    spawn_sync(vec![feed.uuid.clone()]);
+
+    Ok((StatusCode::CREATED, Json(feed)))
+}
+
+async fn remove_feed(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    check_token(&state, &headers)?;
+
+    cli::remove(&id).await.map_err(not_found)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_entrys(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<EntrysQuery>,
+) -> Result<Json<Vec<cli::Entry>>, AppError> {
+    check_token(&state, &headers)?;
+
+    let feed = query.feed.as_deref().filter(|v| !v.trim().is_empty());
+    Ok(Json(
+        cli::entrys(feed, query.unread).await.map_err(not_found)?,
+    ))
+}
+
+async fn set_read(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Json(data): Json<ReadState>,
+) -> Result<Json<cli::Entry>, AppError> {
+    check_token(&state, &headers)?;
+
+    let entry = cli::set_read(&id, data.is_read).await.map_err(not_found)?;
+    Ok(Json(entry))
+}
+
+async fn list_favorites(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<cli::Entry>>, AppError> {
+    check_token(&state, &headers)?;
+    Ok(Json(cli::favorites().await.map_err(internal_error)?))
+}
+
+async fn sync(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SyncQuery>,
+) -> Result<StatusCode, AppError> {
+    check_token(&state, &headers)?;
+
+    let suuids = match query.feed.as_deref().filter(|v| !v.trim().is_empty()) {
+        Some(id) => vec![engine::get().find_feed(id).await.map_err(not_found)?.uuid],
+        _ => engine::get()
+            .feeds()
+            .await
+            .map_err(internal_error)?
+            .into_iter()
+            .map(|item| item.uuid)
+            .collect(),
+    };
+
+    spawn_sync(suuids);
+    Ok(StatusCode::ACCEPTED)
+}
@@ -1,19 +1,30 @@
-use crate::config;
 use crate::slint_generatedAppWindow::AppWindow;
+use crate::{config, db, engine};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 mod about;
+mod account;
+mod backup;
 mod cache;
+pub mod cli;
 mod clipboard;
 mod entry;
+mod event;
 mod find;
+#[cfg(feature = "desktop")]
+pub mod localapi;
 mod message;
 mod ok_cancel_dialog;
+mod opml;
+mod remote;
 mod rss;
 mod setting;
+mod trash;
 mod util;
 
 pub use find::{rss_valid, FindEntry};
+pub use opml::{parse as parse_opml, OpmlEntry};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReqData {
@@ -32,7 +43,13 @@ impl Default for ReqData {
     }
 }
 
+// The database should be initialized before
+pub fn init_engine() {
+    engine::set(engine::Engine::new(db::repo(), Arc::new(remote::NetRemote)));
+}
+
 pub fn init(ui: &AppWindow) {
+    event::init(&ui);
     util::init(&ui);
     clipboard::init(&ui);
     message::init(&ui);
@@ -40,9 +57,13 @@ pub fn init(ui: &AppWindow) {
     cache::init(&ui);
     about::init(&ui);
     setting::init(&ui);
+    account::init(&ui);
+    backup::init(&ui);
+    opml::init(&ui);
 
     // don't adjust functions order
     entry::init(&ui);
     rss::init(&ui);
     find::init(&ui);
+    trash::init(&ui);
 }
@@ -20,13 +20,31 @@ pub fn init(ui: &AppWindow) {
                 "remove-all-cache" => {
                     ui.global::<Logic>().invoke_remove_all_cache();
                 }
+                "remove-cache-older-than" => {
+                    let days = handle_uuid.parse::<i32>().unwrap_or_default();
+                    ui.global::<Logic>().invoke_remove_cache_older_than(days);
+                }
                 "backup-to-remote" => {
                     let setting = ui.global::<Logic>().invoke_get_setting_backup_recover();
                     ui.global::<Logic>().invoke_backup_to_remote(setting);
                 }
-                "recover-from-remote" => {
+                "backup-to-local" => {
+                    let setting = ui.global::<Logic>().invoke_get_setting_backup_recover();
+                    ui.global::<Logic>().invoke_backup_to_local(setting);
+                }
+                "apply-recover" => {
+                    ui.global::<Logic>().invoke_apply_recover();
+                }
+                "remove-local-backup" => {
+                    ui.global::<Logic>().invoke_remove_local_backup(handle_uuid);
+                }
+                "backup-to-webdav" => {
                     let setting = ui.global::<Logic>().invoke_get_setting_backup_recover();
-                    ui.global::<Logic>().invoke_recover_from_remote(setting);
+                    ui.global::<Logic>().invoke_backup_to_webdav(setting);
+                }
+                "remove-webdav-backup" => {
+                    ui.global::<Logic>()
+                        .invoke_remove_webdav_backup(handle_uuid);
                 }
                 _ => (),
             }
@@ -0,0 +1,479 @@
+use super::{
+    message::{async_message_success, async_message_warn},
+    rss,
+};
+use crate::slint_generatedAppWindow::{AppWindow, Logic, OpmlEntry as UIOpmlEntry, Store};
+use crate::{
+    config,
+    db::rss::RssConfig,
+    engine, message_info, message_warn,
+    util::{time::local_now, translator::tr},
+};
+use anyhow::{bail, Result};
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+use slint::{ComponentHandle, Model, VecModel};
+use std::{fs, path::PathBuf};
+
+const CATEGORY_SEPARATOR: &str = "/";
+const RSSBOX_NAMESPACE: &str = "https://github.com/Heng30/rssbox-android";
+
+#[macro_export]
+macro_rules! store_opml_entrys {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_opml_entrys()
+            .as_any()
+            .downcast_ref::<VecModel<UIOpmlEntry>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OpmlEntry {
+    pub name: String,
+    pub url: String,
+
+    // the names of the parent outlines, joined by `/`
+    pub category: String,
+
+    // the `htmlUrl` and `description` attributes
+    pub site_url: String,
+    pub description: String,
+
+    // the custom attributes of rssbox, other readers don't have them
+    pub feed_format: Option<String>,
+    pub use_http_proxy: bool,
+    pub use_socks5_proxy: bool,
+}
+
+impl From<OpmlEntry> for RssConfig {
+    fn from(entry: OpmlEntry) -> Self {
+        RssConfig {
+            uuid: String::default(),
+            name: entry.name,
+            url: entry.url,
+            icon_index: 0,
+            use_http_proxy: entry.use_http_proxy,
+            use_socks5_proxy: entry.use_socks5_proxy,
+            is_favorite: false,
+            feed_format: entry.feed_format.unwrap_or("AUTO".to_string()),
+            category: entry.category,
+            remote_id: String::default(),
+        }
+    }
+}
+
+impl From<UIOpmlEntry> for OpmlEntry {
+    fn from(entry: UIOpmlEntry) -> Self {
+        OpmlEntry {
+            name: entry.name.into(),
+            url: entry.url.into(),
+            category: entry.category.into(),
+            site_url: String::default(),
+            description: String::default(),
+            feed_format: if entry.feed_format.is_empty() {
+                None
+            } else {
+                Some(entry.feed_format.into())
+            },
+            use_http_proxy: entry.use_http_proxy,
+            use_socks5_proxy: entry.use_socks5_proxy,
+        }
+    }
+}
+
+pub fn init(ui: &AppWindow) {
+    store_opml_entrys!(ui).set_vec(vec![]);
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_preview_opml(move |path| {
+        let ui = ui_handle.unwrap();
+
+        let items = match fs::read_to_string(path.trim()) {
+            Ok(text) => match parse(&text) {
+                Ok(items) => items,
+                Err(e) => {
+                    message_warn!(ui, format!("{}. {}: {e:?}", tr("解析失败"), tr("原因")));
+                    return;
+                }
+            },
+            Err(e) => {
+                message_warn!(ui, format!("{}. {}: {e:?}", tr("读取失败"), tr("原因")));
+                return;
+            }
+        };
+
+        if items.is_empty() {
+            message_info!(ui, tr("没有订阅"));
+        }
+
+        let items = items
+            .into_iter()
+            .map(|item| {
+                let is_exist = rss::is_exist_url(&ui, &item.url);
+                UIOpmlEntry {
+                    name: item.name.into(),
+                    url: item.url.into(),
+                    category: item.category.into(),
+                    feed_format: item.feed_format.unwrap_or_default().into(),
+                    use_http_proxy: item.use_http_proxy,
+                    use_socks5_proxy: item.use_socks5_proxy,
+                    is_exist,
+                    checked: !is_exist,
+                }
+            })
+            .collect::<Vec<UIOpmlEntry>>();
+
+        store_opml_entrys!(ui).set_vec(items);
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_toggle_opml_entry(move |index| {
+        let ui = ui_handle.unwrap();
+        let index = index as usize;
+
+        if let Some(mut item) = ui.global::<Store>().get_opml_entrys().row_data(index) {
+            if item.is_exist {
+                return;
+            }
+
+            item.checked = !item.checked;
+            store_opml_entrys!(ui).set_row_data(index, item);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_import_opml(move || {
+        let ui = ui_handle.unwrap();
+
+        let items = ui
+            .global::<Store>()
+            .get_opml_entrys()
+            .iter()
+            .filter(|item| item.checked && !rss::is_exist_url(&ui, &item.url))
+            .map(|item| OpmlEntry::from(item).into())
+            .collect::<Vec<RssConfig>>();
+
+        if items.is_empty() {
+            message_info!(ui, tr("没有订阅"));
+            return;
+        }
+
+        store_opml_entrys!(ui).set_vec(vec![]);
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            match _import_opml(items).await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("导入失败"), tr("原因")),
+                ),
+                Ok(counts) => {
+                    async_message_success(ui.clone(), format!("{}: {counts}", tr("导入成功")));
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        rss::init_rss(&ui.unwrap());
+                    });
+                }
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_export_opml(move |path| {
+        let ui = ui_handle.unwrap();
+        let items = rss::get_rss_configs(&ui);
+
+        let path = if path.trim().is_empty() {
+            config::backup_dir().join(format!("rssbox-{}.opml", local_now("%Y%m%d-%H%M%S")))
+        } else {
+            PathBuf::from(path.trim())
+        };
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            let res = match path.parent() {
+                Some(dir) => fs::create_dir_all(dir).and_then(|_| fs::write(&path, export(&items))),
+                _ => fs::write(&path, export(&items)),
+            };
+
+            match res {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("导出失败"), tr("原因")),
+                ),
+                _ => async_message_success(
+                    ui.clone(),
+                    format!("{}: {}", tr("导出成功"), path.display()),
+                ),
+            }
+        });
+    });
+}
+
+// The feeds are subscribed in the server too, and the duplicated urls are skipped
+pub(super) async fn _import_opml(items: Vec<RssConfig>) -> Result<usize> {
+    let added = engine::get().add_feeds(items).await?;
+    for (url, msg) in added.failures.iter() {
+        log::warn!("{url}: {msg}");
+    }
+
+    Ok(added.feeds.len())
+}
+
+fn outline_attributes(e: &BytesStart) -> Result<(Vec<(String, String)>, bool)> {
+    let mut attrs = vec![];
+
+    for attr in e.attributes() {
+        let attr = attr?;
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        let value = attr.unescape_value()?.trim().to_string();
+        attrs.push((key, value));
+    }
+
+    let is_feed = attrs
+        .iter()
+        .any(|(k, v)| k.eq_ignore_ascii_case("xmlUrl") && !v.is_empty());
+
+    Ok((attrs, is_feed))
+}
+
+fn attribute<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+        .filter(|v| !v.is_empty())
+}
+
+fn to_opml_entry(attrs: &[(String, String)], categorys: &[String]) -> OpmlEntry {
+    let url = attribute(attrs, "xmlUrl").unwrap_or_default().to_string();
+    let name = attribute(attrs, "text")
+        .or(attribute(attrs, "title"))
+        .unwrap_or(&url)
+        .to_string();
+
+    OpmlEntry {
+        name,
+        url,
+        category: categorys.join(CATEGORY_SEPARATOR),
+        site_url: attribute(attrs, "htmlUrl").unwrap_or_default().to_string(),
+        description: attribute(attrs, "description")
+            .unwrap_or_default()
+            .to_string(),
+        feed_format: attribute(attrs, "rssbox:feedFormat").map(|v| v.to_uppercase()),
+        use_http_proxy: attribute(attrs, "rssbox:useHttpProxy") == Some("true"),
+        use_socks5_proxy: attribute(attrs, "rssbox:useSocks5Proxy") == Some("true"),
+    }
+}
+
+// Support OPML 1.0 and 2.0. The nested outlines are flattened, and the names
+// of the parent outlines are kept as the category.
+pub fn parse(text: &str) -> Result<Vec<OpmlEntry>> {
+    let mut reader = Reader::from_str(text);
+    reader.trim_text(true);
+
+    let (mut items, mut categorys, mut stack) = (vec![], vec![], vec![]);
+    let mut is_opml = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"opml" => is_opml = true,
+                b"outline" => {
+                    let (attrs, is_feed) = outline_attributes(&e)?;
+                    if is_feed {
+                        items.push(to_opml_entry(&attrs, &categorys));
+                    }
+
+                    // a feed outline may contain other outlines, but it is not a category
+                    let category = if is_feed {
+                        None
+                    } else {
+                        attribute(&attrs, "text")
+                            .or(attribute(&attrs, "title"))
+                            .map(|v| v.to_string())
+                    };
+
+                    if let Some(ref category) = category {
+                        categorys.push(category.clone());
+                    }
+                    stack.push(category.is_some());
+                }
+                _ => (),
+            },
+            Event::Empty(e) if e.name().as_ref() == b"outline" => {
+                let (attrs, is_feed) = outline_attributes(&e)?;
+                if is_feed {
+                    items.push(to_opml_entry(&attrs, &categorys));
+                }
+            }
+            Event::End(e) if e.name().as_ref() == b"outline" && stack.pop() == Some(true) => {
+                categorys.pop();
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    if !is_opml {
+        bail!("not an opml document");
+    }
+
+    Ok(items)
+}
+
+fn feed_outline(item: &RssConfig) -> String {
+    let feed_type = if item.feed_format == "ATOM" {
+        "atom"
+    } else {
+        "rss"
+    };
+
+    format!(
+        r#"<outline type="{feed_type}" text="{name}" title="{name}" xmlUrl="{url}" rssbox:feedFormat="{format}" rssbox:useHttpProxy="{http}" rssbox:useSocks5Proxy="{socks5}"/>"#,
+        name = escape(&item.name),
+        url = escape(&item.url),
+        format = escape(&item.feed_format),
+        http = item.use_http_proxy,
+        socks5 = item.use_socks5_proxy,
+    )
+}
+
+// The feeds are grouped by their categories. The `feed_format` and proxy flags
+// are kept as the custom attributes, other readers will ignore them.
+pub fn export(items: &[RssConfig]) -> String {
+    let mut categorys: Vec<(&str, Vec<&RssConfig>)> = vec![];
+    for item in items.iter() {
+        match categorys
+            .iter_mut()
+            .find(|(category, _)| *category == item.category)
+        {
+            Some((_, list)) => list.push(item),
+            _ => categorys.push((item.category.as_str(), vec![item])),
+        }
+    }
+
+    let mut body = String::default();
+    for (category, list) in categorys.into_iter() {
+        if category.is_empty() {
+            for item in list.into_iter() {
+                body.push_str(&format!("    {}\n", feed_outline(item)));
+            }
+        } else {
+            body.push_str(&format!(
+                "    <outline text=\"{0}\" title=\"{0}\">\n",
+                escape(category)
+            ));
+            for item in list.into_iter() {
+                body.push_str(&format!("      {}\n", feed_outline(item)));
+            }
+            body.push_str("    </outline>\n");
+        }
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0" xmlns:rssbox="{RSSBOX_NAMESPACE}">
+  <head>
+    <title>RssBox</title>
+    <dateCreated>{}</dateCreated>
+  </head>
+  <body>
+{body}  </body>
+</opml>
+"#,
+        local_now("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPML_1: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="1.0">
+  <head><title>Subscriptions</title></head>
+  <body>
+    <outline text="Tech">
+      <outline text="Rust" title="Rust">
+        <outline type="rss" text="This Week in Rust" xmlUrl="https://this-week-in-rust.org/rss.xml" htmlUrl="https://this-week-in-rust.org" description="Rust news"/>
+      </outline>
+      <outline type="rss" title="Hacker News" xmlUrl="https://news.ycombinator.com/rss"></outline>
+    </outline>
+    <outline type="rss" text="No Category &amp; More" xmlUrl="https://example.com/feed?a=1&amp;b=2"/>
+    <outline text="Empty"/>
+  </body>
+</opml>"#;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let items = parse(OPML_1)?;
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].name, "This Week in Rust");
+        assert_eq!(items[0].category, "Tech/Rust");
+        assert_eq!(items[0].site_url, "https://this-week-in-rust.org");
+        assert_eq!(items[0].description, "Rust news");
+        assert_eq!(items[1].name, "Hacker News");
+        assert_eq!(items[1].category, "Tech");
+        assert_eq!(items[2].name, "No Category & More");
+        assert_eq!(items[2].url, "https://example.com/feed?a=1&b=2");
+        assert_eq!(items[2].category, "");
+        assert!(items[2].feed_format.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(parse("<rss></rss>").is_err());
+        assert!(parse("<opml><body><outline></body></opml>").is_err());
+    }
+
+    #[test]
+    fn test_export() -> Result<()> {
+        let items = vec![
+            RssConfig {
+                uuid: "uuid-1".to_string(),
+                name: "Rust <Blog>".to_string(),
+                url: "https://blog.rust-lang.org/feed.xml".to_string(),
+                icon_index: 0,
+                use_http_proxy: true,
+                use_socks5_proxy: false,
+                is_favorite: false,
+                feed_format: "ATOM".to_string(),
+                category: "Tech/Rust".to_string(),
+                remote_id: String::default(),
+            },
+            RssConfig {
+                uuid: "uuid-2".to_string(),
+                name: "News".to_string(),
+                url: "https://example.com/rss?a=1&b=2".to_string(),
+                icon_index: 0,
+                use_http_proxy: false,
+                use_socks5_proxy: true,
+                is_favorite: false,
+                feed_format: "AUTO".to_string(),
+                category: String::default(),
+                remote_id: String::default(),
+            },
+        ];
+
+        let items_parsed = parse(&export(&items))?;
+        assert_eq!(items_parsed.len(), 2);
+
+        assert_eq!(items_parsed[0].name, "Rust <Blog>");
+        assert_eq!(items_parsed[0].category, "Tech/Rust");
+        assert_eq!(items_parsed[0].feed_format, Some("ATOM".to_string()));
+        assert!(items_parsed[0].use_http_proxy);
+
+        assert_eq!(items_parsed[1].url, "https://example.com/rss?a=1&b=2");
+        assert_eq!(items_parsed[1].category, "");
+        assert!(items_parsed[1].use_socks5_proxy);
+        Ok(())
+    }
+}
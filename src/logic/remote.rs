@@ -0,0 +1,36 @@
+use super::{account, rss};
+use crate::db::{entry::RssEntry, rss::RssConfig};
+use crate::engine::Remote;
+use anyhow::Result;
+use async_trait::async_trait;
+
+// Fetch the feeds directly, or from the server if the account is enabled
+pub struct NetRemote;
+
+#[async_trait]
+impl Remote for NetRemote {
+    async fn fetch_entrys(&self, rss: &RssConfig) -> Result<Vec<RssEntry>> {
+        // the feed falls back to the direct fetch when the account has been disabled
+        if rss.remote_id.is_empty() || !account::is_enabled() {
+            rss::fetch_entrys(rss.clone().into()).await
+        } else {
+            account::fetch_entrys(&rss.uuid, &rss.remote_id).await
+        }
+    }
+
+    async fn saved(&self, rss: &RssConfig) -> Result<()> {
+        account::saved(&rss.remote_id).await
+    }
+
+    async fn subscribe(&self, rss: &RssConfig) -> Result<String> {
+        account::subscribe(rss).await
+    }
+
+    async fn unsubscribe(&self, rss: &RssConfig) -> Result<()> {
+        account::unsubscribe(&rss.remote_id).await
+    }
+
+    async fn set_read(&self, uuids: Vec<String>, is_read: bool) -> Result<()> {
+        account::_set_read(uuids, is_read).await
+    }
+}
@@ -1,22 +1,24 @@
 use super::message::{async_message_success, async_message_warn};
 use crate::slint_generatedAppWindow::{
-    AppWindow, Logic, RssConfig as UIRssConfig, RssEntry as UIRssEntry, Store,
+    AppWindow, FeedPreview as UIFeedPreview, Logic, RssConfig as UIRssConfig,
+    RssEntry as UIRssEntry, Store,
 };
 use crate::{
     config,
-    db::{self, entry::RssEntry, rss::RssConfig, ComEntry},
-    message_info, message_success, store_rss_entrys,
+    db::{self, entry::RssEntry, rss::RssConfig, ComEntry, Repository},
+    engine, message_info, message_success, message_warn, store_rss_entrys,
     util::{self, crypto::md5_hex, http, translator::tr},
 };
 use anyhow::{Context, Result};
 use atom_syndication::{Feed, FixedDateTime, Link, TextType};
 use html2text;
 use rss::Channel;
-use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel, Weak};
+use slint::{ComponentHandle, Model, ModelRc, SharedString, VecModel};
 use std::{cmp::Ordering, io::BufReader, time::Duration};
 use uuid::Uuid;
 
 const EMPTY_UUID: &str = "empty-uuid";
+const MAX_PREVIEW_ENTRYS: usize = 10;
 
 #[derive(Debug, Default, Clone)]
 pub struct SyncItem {
@@ -26,6 +28,15 @@ pub struct SyncItem {
     pub feed_format: String,
 }
 
+// The channel information and the entrys of a feed
+#[derive(Debug, Default, Clone)]
+struct FeedInfo {
+    title: String,
+    description: String,
+    feed_format: String,
+    entrys: Vec<RssEntry>,
+}
+
 #[derive(Debug, Clone)]
 struct ErrorMsg {
     url: String,
@@ -34,10 +45,16 @@ struct ErrorMsg {
 
 impl From<UIRssConfig> for SyncItem {
     fn from(rss: UIRssConfig) -> Self {
+        RssConfig::from(rss).into()
+    }
+}
+
+impl From<RssConfig> for SyncItem {
+    fn from(rss: RssConfig) -> Self {
         SyncItem {
-            suuid: rss.uuid.to_string(),
-            url: rss.url.to_string(),
-            feed_format: rss.feed_format.to_string(),
+            suuid: rss.uuid,
+            url: rss.url,
+            feed_format: rss.feed_format,
             proxy_type: if rss.use_http_proxy {
                 "Http".to_string()
             } else if rss.use_socks5_proxy {
@@ -178,7 +195,7 @@ fn rss_config_sort_fn(a: &UIRssConfig, b: &UIRssConfig) -> Ordering {
     }
 }
 
-fn rss_config_sort(ui: &AppWindow) {
+pub fn rss_config_sort(ui: &AppWindow) {
     let mut list = ui
         .global::<Store>()
         .get_rss_lists()
@@ -189,16 +206,100 @@ fn rss_config_sort(ui: &AppWindow) {
     store_rss_lists!(ui).set_vec(list);
 }
 
-pub fn remove_all_rss(ui: &AppWindow) {
-    let uuids = ui
+// Show the rss which has been saved in the database
+pub fn push_rss(ui: &AppWindow, rss: RssConfig) {
+    let rss: UIRssConfig = rss.into();
+
+    let _assert = rss
+        .entry
+        .as_any()
+        .downcast_ref::<VecModel<UIRssEntry>>()
+        .expect("We know we set a VecModel earlier");
+
+    let mut list = ui
         .global::<Store>()
         .get_rss_lists()
         .iter()
-        .map(|item| item.uuid)
         .collect::<Vec<_>>();
 
-    for uuid in uuids.into_iter() {
-        ui.global::<Logic>().invoke_remove_rss(uuid);
+    if list.is_empty() {
+        ui.global::<Store>().set_rss_entrys(rss.entry.clone());
+        ui.global::<Store>().set_current_rss_uuid(rss.uuid.clone());
+    }
+
+    list.push(rss);
+    list.sort_by(rss_config_sort_fn);
+
+    store_rss_lists!(ui).set_vec(list);
+}
+
+// Show the rss config which has been saved in the database
+pub fn update_rss(ui: &AppWindow, rss: RssConfig) {
+    for (index, mut item) in ui.global::<Store>().get_rss_lists().iter().enumerate() {
+        if item.uuid != rss.uuid {
+            continue;
+        }
+
+        item.name = rss.name.into();
+        item.url = rss.url.into();
+        item.icon_index = rss.icon_index;
+        item.use_http_proxy = rss.use_http_proxy;
+        item.use_socks5_proxy = rss.use_socks5_proxy;
+        item.is_favorite = rss.is_favorite;
+        item.feed_format = rss.feed_format.into();
+        item.category = rss.category.into();
+        item.remote_id = rss.remote_id.into();
+        store_rss_lists!(ui).set_row_data(index, item);
+
+        rss_config_sort(ui);
+        return;
+    }
+}
+
+// Hide the rss which has been removed from the database
+pub fn pop_rss(ui: &AppWindow, uuid: &str) {
+    for (index, rss) in ui.global::<Store>().get_rss_lists().iter().enumerate() {
+        if rss.uuid != uuid {
+            continue;
+        }
+
+        store_rss_lists!(ui).remove(index);
+
+        if ui.global::<Store>().get_current_rss_uuid() == uuid {
+            notify_ui_update_unread_counts(ui);
+            ui.global::<Logic>()
+                .invoke_switch_rss(uuid.into(), EMPTY_UUID.into());
+        }
+        return;
+    }
+}
+
+// Show the read state which has been saved in the database
+pub fn update_entry_read(ui: &AppWindow, suuid: &str, uuid: &str, is_read: bool) {
+    for (index, mut rss) in ui.global::<Store>().get_rss_lists().iter().enumerate() {
+        if rss.uuid != suuid {
+            continue;
+        }
+
+        for (row, mut entry) in rss.entry.iter().enumerate() {
+            if entry.uuid != uuid || entry.is_read == is_read {
+                continue;
+            }
+
+            entry.is_read = is_read;
+            rss.entry.set_row_data(row, entry);
+
+            rss.unread_counts = if is_read {
+                i32::max(0, rss.unread_counts - 1)
+            } else {
+                rss.unread_counts + 1
+            };
+            store_rss_lists!(ui).set_row_data(index, rss);
+
+            notify_ui_update_unread_counts(ui);
+            return;
+        }
+        return;
     }
 }
 
@@ -218,7 +319,7 @@ pub fn init_rss(ui: &AppWindow) {
 
     let ui_handle = ui.as_weak();
     tokio::spawn(async move {
-        match db::rss::select_all().await {
+        match db::repo().select_all_rss().await {
             Ok(items) => {
                 let config_list = init_rss_configs(items).await;
                 let entry_list = init_rss_entrys(&config_list).await;
@@ -293,7 +394,7 @@ pub fn init(ui: &AppWindow) {
 
         let ui = ui.as_weak();
         tokio::spawn(async move {
-            let rss = match _new_rss(rss).await {
+            let rss = match engine::get().add_feed(rss).await {
                 Err(e) => {
                     async_message_warn(
                         ui.clone(),
@@ -306,33 +407,81 @@ pub fn init(ui: &AppWindow) {
 
             let _ = slint::invoke_from_event_loop(move || {
                 let ui = ui.clone().unwrap();
-                let rss: UIRssConfig = rss.into();
                 let suuid = rss.uuid.clone();
 
-                let _assert = rss
-                    .entry
-                    .as_any()
-                    .downcast_ref::<VecModel<UIRssEntry>>()
-                    .expect("We know we set a VecModel earlier");
+                // the event of the new rss may be handled later than here
+                if get_rss_config(&ui, &suuid).is_none() {
+                    push_rss(&ui, rss);
+                }
+                message_success!(ui, tr("新建成功"));
 
-                let mut list = ui
-                    .global::<Store>()
-                    .get_rss_lists()
-                    .iter()
-                    .collect::<Vec<_>>();
+                ui.global::<Logic>().invoke_sync_rss(suuid.into(), true);
+            });
+        });
+    });
 
-                if list.is_empty() {
-                    ui.global::<Store>().set_rss_entrys(rss.entry.clone());
-                    ui.global::<Store>().set_current_rss_uuid(rss.uuid.clone());
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_preview_rss(move |config| {
+        let ui = ui_handle.unwrap();
+
+        ui.global::<Store>().set_feed_preview(UIFeedPreview {
+            is_show: true,
+            is_loading: true,
+            config: config.clone(),
+            ..Default::default()
+        });
+
+        let (rss, sync_item) = (RssConfig::from(config.clone()), SyncItem::from(config));
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            let feed = fetch_feed(&sync_item).await;
+
+            let _ = slint::invoke_from_event_loop(move || {
+                let ui = ui.unwrap();
+
+                // the preview has been closed or replaced by another one
+                let preview = ui.global::<Store>().get_feed_preview();
+                if !preview.is_show || preview.config.url.as_str() != rss.url {
+                    return;
                 }
 
-                list.push(rss);
-                list.sort_by(rss_config_sort_fn);
+                let feed = match feed {
+                    Ok(feed) => feed,
+                    Err(e) => {
+                        ui.global::<Store>()
+                            .set_feed_preview(UIFeedPreview::default());
+                        message_warn!(ui, format!("{}. {}: {e:?}", tr("预览失败"), tr("原因")));
+                        return;
+                    }
+                };
 
-                store_rss_lists!(ui).set_vec(list);
-                message_success!(ui, tr("新建成功"));
+                let pub_dates = feed
+                    .entrys
+                    .iter()
+                    .map(|item| item.pub_date.clone())
+                    .collect::<Vec<_>>();
+
+                let mut rss = rss;
+                rss.feed_format = feed.feed_format;
+                if !feed.title.is_empty() {
+                    rss.name = feed.title;
+                }
 
-                ui.global::<Logic>().invoke_sync_rss(suuid, true);
+                let entrys = feed
+                    .entrys
+                    .into_iter()
+                    .take(MAX_PREVIEW_ENTRYS)
+                    .map(|item| item.into())
+                    .collect::<Vec<UIRssEntry>>();
+
+                ui.global::<Store>().set_feed_preview(UIFeedPreview {
+                    is_show: true,
+                    is_loading: false,
+                    config: rss.into(),
+                    description: feed.description.into(),
+                    frequency: posting_frequency_text(&pub_dates).into(),
+                    entrys: ModelRc::new(VecModel::from(entrys)),
+                });
             });
         });
     });
@@ -355,7 +504,7 @@ pub fn init(ui: &AppWindow) {
             let ui = ui.as_weak();
             let rss = RssConfig::from(rss);
             tokio::spawn(async move {
-                match _edit_rss(rss).await {
+                match engine::get().update_feed(rss).await {
                     Err(e) => async_message_warn(
                         ui.clone(),
                         format!("{}. {}: {e:?}", tr("编辑失败"), tr("原因")),
@@ -392,7 +541,7 @@ pub fn init(ui: &AppWindow) {
 
             let ui = ui.as_weak();
             tokio::spawn(async move {
-                match _remove_rss(uuid.as_str()).await {
+                match engine::get().remove_feed(uuid.as_str()).await {
                     Err(e) => async_message_warn(
                         ui.clone(),
                         format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
@@ -477,7 +626,7 @@ pub fn init(ui: &AppWindow) {
             let rss = RssConfig::from(rss);
             tokio::spawn(async move {
                 let is_favorite = rss.is_favorite;
-                match _toggle_rss_favorite(rss).await {
+                match engine::get().update_feed(rss).await {
                     Err(e) => async_message_warn(
                         ui.clone(),
                         format!(
@@ -552,7 +701,7 @@ pub fn init(ui: &AppWindow) {
 
                 let ui = ui.as_weak();
                 tokio::spawn(async move {
-                    let error_msgs = sync_rss(ui.clone(), items).await;
+                    let error_msgs = sync_rss(items).await;
 
                     if is_show_toast {
                         if error_msgs.is_empty() {
@@ -582,39 +731,130 @@ pub fn init(ui: &AppWindow) {
         let ui = ui_handle.unwrap();
         message_info!(ui, tr("正在同步..."));
 
-        for item in ui.global::<Store>().get_rss_lists().iter() {
-            ui.global::<Logic>().invoke_sync_rss(item.uuid, false);
+        if super::account::is_enabled() {
+            super::account::sync_all(ui.as_weak(), get_rss_configs(&ui));
+        } else {
+            sync_rss_items(&ui);
         }
     });
 }
 
-async fn _new_rss(mut rss: RssConfig) -> Result<RssConfig> {
-    rss.uuid = Uuid::new_v4().to_string().into();
-    let config = serde_json::to_string(&rss)?;
-    db::rss::insert(rss.uuid.as_str(), &config).await?;
-    db::entry::new(rss.uuid.as_str()).await?;
-    Ok(rss)
-}
+// Subscribe the rss in one database transaction, and then sync them at once
+// The `on_added` is called in the event loop with the feeds which have been added
+pub fn new_rss_list(
+    ui: &AppWindow,
+    items: Vec<RssConfig>,
+    on_added: impl FnOnce(&AppWindow, &[RssConfig]) + Send + 'static,
+) {
+    let mut rss_list: Vec<RssConfig> = vec![];
+    for item in items.into_iter() {
+        if is_exist_url(ui, &item.url)
+            || rss_list
+                .iter()
+                .any(|rss| util::url::is_same(&rss.url, &item.url))
+        {
+            continue;
+        }
+        rss_list.push(item);
+    }
 
-async fn _edit_rss(rss: RssConfig) -> Result<()> {
-    let config = serde_json::to_string(&rss)?;
-    db::rss::update(rss.uuid.as_str(), &config).await?;
-    Ok(())
-}
+    if rss_list.is_empty() {
+        message_info!(ui, tr("请勿重复添加"));
+        return;
+    }
+
+    message_info!(ui, tr("正在订阅..."));
+
+    let total = rss_list.len();
+    let ui = ui.as_weak();
+    tokio::spawn(async move {
+        let added = match engine::get().add_feeds(rss_list).await {
+            Err(e) => {
+                async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("新建失败"), tr("原因")),
+                );
+                return;
+            }
+            Ok(added) => added,
+        };
+
+        for (url, msg) in added.failures.iter() {
+            log::warn!("{url}: {msg}");
+        }
+
+        if !added.failures.is_empty() {
+            async_message_warn(
+                ui.clone(),
+                format!("{}: {}/{total}", tr("新建失败"), added.failures.len()),
+            );
+        }
+
+        let rss_list = added.feeds;
+        if rss_list.is_empty() {
+            return;
+        }
 
-async fn _remove_rss(uuid: &str) -> Result<()> {
-    db::rss::delete(uuid).await?;
-    db::entry::drop_table(uuid).await?;
-    Ok(())
+        let counts = rss_list.len();
+        let sync_items = rss_list
+            .iter()
+            .map(|rss| SyncItem::from(rss.clone()))
+            .collect::<Vec<_>>();
+
+        let ui_handle = ui.clone();
+        let _ = slint::invoke_from_event_loop(move || {
+            let ui = ui_handle.unwrap();
+            let update_time = util::time::local_now("%H:%M:%S");
+
+            let mut list = ui
+                .global::<Store>()
+                .get_rss_lists()
+                .iter()
+                .collect::<Vec<_>>();
+
+            // the events of the new rss may be handled earlier than here
+            let is_empty = list.is_empty();
+            for rss in rss_list.iter() {
+                if list.iter().any(|item| item.uuid == rss.uuid) {
+                    continue;
+                }
+
+                let mut rss: UIRssConfig = rss.clone().into();
+                rss.update_time = update_time.clone().into();
+                list.push(rss);
+            }
+            list.sort_by(rss_config_sort_fn);
+
+            if is_empty {
+                ui.global::<Store>().set_rss_entrys(list[0].entry.clone());
+                ui.global::<Store>()
+                    .set_current_rss_uuid(list[0].uuid.clone());
+            }
+
+            store_rss_lists!(ui).set_vec(list);
+            on_added(&ui, &rss_list);
+            message_success!(ui, format!("{}: {counts}", tr("新建成功")));
+        });
+
+        let error_msgs = sync_rss(sync_items).await;
+        if error_msgs.is_empty() {
+            async_message_success(ui.clone(), tr("同步成功"));
+        } else {
+            async_message_warn(
+                ui.clone(),
+                format!("{}: {}/{counts}", tr("同步失败"), error_msgs.len()),
+            );
+        }
+    });
 }
 
-async fn _toggle_rss_favorite(rss: RssConfig) -> Result<()> {
-    let config = serde_json::to_string(&rss)?;
-    db::rss::update(&rss.uuid.as_str(), &config).await?;
-    Ok(())
+pub fn sync_rss_items(ui: &AppWindow) {
+    for item in ui.global::<Store>().get_rss_lists().iter() {
+        ui.global::<Logic>().invoke_sync_rss(item.uuid, false);
+    }
 }
 
-fn parse_summary(summary: &str, is_text: bool) -> String {
+pub fn parse_summary(summary: &str, is_text: bool) -> String {
     let mut max_counts = 100;
     let summary = summary.trim();
 
@@ -650,7 +890,44 @@ fn parse_summary(summary: &str, is_text: bool) -> String {
     }
 }
 
-fn parse_rss(suuid: &str, content: Vec<u8>) -> Result<Vec<RssEntry>> {
+fn parse_description(description: &str, is_text: bool) -> String {
+    if description.trim().is_empty() {
+        String::default()
+    } else {
+        parse_summary(description, is_text)
+    }
+}
+
+// The average posts per day of the entrys. `None` means it can't be calculated.
+fn posting_frequency(pub_dates: &[String]) -> Option<f64> {
+    let mut dates = pub_dates
+        .iter()
+        .filter_map(|item| util::time::parse_pub_date(item))
+        .collect::<Vec<_>>();
+
+    if dates.len() < 2 {
+        return None;
+    }
+
+    dates.sort();
+    let days = (dates[dates.len() - 1] - dates[0]).num_seconds() as f64 / 86400.0;
+    if days <= 0.0 {
+        return None;
+    }
+
+    Some((dates.len() - 1) as f64 / days)
+}
+
+fn posting_frequency_text(pub_dates: &[String]) -> String {
+    match posting_frequency(pub_dates) {
+        None => tr("未知"),
+        Some(v) if v >= 1.0 => format!("{:.1} {}", v, tr("篇/天")),
+        Some(v) if v * 7.0 >= 1.0 => format!("{:.1} {}", v * 7.0, tr("篇/周")),
+        Some(v) => format!("{:.1} {}", v * 30.0, tr("篇/月")),
+    }
+}
+
+fn parse_rss(suuid: &str, content: Vec<u8>) -> Result<FeedInfo> {
     let mut entrys = vec![];
     let ch = Channel::read_from(&content[..]).context("failed to parse rss xml")?;
 
@@ -693,10 +970,15 @@ fn parse_rss(suuid: &str, content: Vec<u8>) -> Result<Vec<RssEntry>> {
         });
     }
 
-    Ok(entrys)
+    Ok(FeedInfo {
+        title: ch.title().trim().to_string(),
+        description: parse_description(ch.description(), false),
+        feed_format: "RSS".to_string(),
+        entrys,
+    })
 }
 
-fn parse_atom(suuid: &str, content: Vec<u8>) -> Result<Vec<RssEntry>> {
+fn parse_atom(suuid: &str, content: Vec<u8>) -> Result<FeedInfo> {
     let mut entrys = vec![];
     let feed = Feed::read_from(BufReader::new(&content[..])).context("failed to parse atom xml")?;
 
@@ -751,10 +1033,19 @@ fn parse_atom(suuid: &str, content: Vec<u8>) -> Result<Vec<RssEntry>> {
         });
     }
 
-    Ok(entrys)
+    Ok(FeedInfo {
+        title: feed.title().as_str().trim().to_string(),
+        description: match feed.subtitle() {
+            Some(s) => parse_description(s.as_str(), s.r#type == TextType::Text),
+            _ => String::default(),
+        },
+        feed_format: "ATOM".to_string(),
+        entrys,
+    })
 }
 
-async fn fetch_entrys(sync_item: SyncItem) -> Result<Vec<RssEntry>> {
+// Download and parse the feed, nothing is written to the database
+async fn fetch_feed(sync_item: &SyncItem) -> Result<FeedInfo> {
     let request_timeout = u64::max(config::sync().sync_timeout as u64, 10_u64);
 
     let client = http::client(Some(sync_item.proxy_type.as_str().into()))?;
@@ -768,18 +1059,36 @@ async fn fetch_entrys(sync_item: SyncItem) -> Result<Vec<RssEntry>> {
         .await?
         .to_vec();
 
-    let entrys = match sync_item.feed_format.to_lowercase().as_str() {
+    Ok(match sync_item.feed_format.to_lowercase().as_str() {
         "rss" => parse_rss(sync_item.suuid.as_str(), content)?,
         "atom" => parse_atom(sync_item.suuid.as_str(), content)?,
         _ => match parse_rss(sync_item.suuid.as_str(), content.clone()) {
             Ok(v) => v,
             _ => parse_atom(sync_item.suuid.as_str(), content)?,
         },
-    };
+    })
+}
+
+pub(super) async fn fetch_title(sync_item: &SyncItem) -> Result<String> {
+    Ok(fetch_feed(sync_item).await?.title)
+}
+
+pub(super) async fn fetch_entrys(sync_item: SyncItem) -> Result<Vec<RssEntry>> {
+    let entrys = fetch_feed(&sync_item).await?.entrys;
+
+    let unique_entrys = skip_trash_entrys(db::repo().as_ref(), entrys).await;
+    Ok(unique_entrys.into_iter().rev().collect())
+}
 
+// The entrys which have been removed by the user are not shown again
+pub(super) async fn skip_trash_entrys(
+    repo: &dyn Repository,
+    entrys: Vec<RssEntry>,
+) -> Vec<RssEntry> {
     let mut unique_entrys = vec![];
     for item in entrys.into_iter() {
-        if db::trash::is_exist(&md5_hex(item.url.as_str()))
+        if repo
+            .is_exist_trash(&md5_hex(item.url.as_str()))
             .await
             .is_err()
         {
@@ -787,35 +1096,68 @@ async fn fetch_entrys(sync_item: SyncItem) -> Result<Vec<RssEntry>> {
         }
     }
 
-    let unique_entrys = unique_entrys.into_iter().rev().collect();
-    Ok(unique_entrys)
+    unique_entrys
 }
 
-async fn sync_rss(ui: Weak<AppWindow>, items: Vec<SyncItem>) -> Vec<ErrorMsg> {
+// The new entrys are shown by the events of the engine
+async fn sync_rss(items: Vec<SyncItem>) -> Vec<ErrorMsg> {
     let mut error_msgs = vec![];
 
     for item in items.into_iter() {
-        let (suuid, url) = (item.suuid.clone(), item.url.clone());
-
-        match fetch_entrys(item).await {
-            Ok(entrys) => {
-                let ui = ui.clone();
-                let _ = slint::invoke_from_event_loop(move || {
-                    let ui = ui.unwrap();
-                    super::entry::update_new_entrys(&ui, suuid.as_str(), entrys);
-
-                    if suuid.as_str() == ui.global::<Store>().get_current_rss_uuid().as_str() {
-                        notify_ui_update_unread_counts(&ui);
-                        ui.global::<Store>().invoke_refresh_current_tab();
-                    }
-                });
-            }
-            Err(e) => error_msgs.push(ErrorMsg {
-                url,
+        if let Err(e) = engine::get().sync(&item.suuid).await {
+            error_msgs.push(ErrorMsg {
+                url: item.url,
                 msg: format!("{e:?}"),
-            }),
+            });
         }
     }
 
     error_msgs
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss() -> Result<()> {
+        let text = r#"<?xml version="1.0"?>
+<rss version="2.0">
+  <channel>
+    <title> Rust Blog </title>
+    <link>https://blog.rust-lang.org</link>
+    <description>Empowering everyone to build reliable and efficient software.</description>
+    <item>
+      <title>Hello</title>
+      <link>https://blog.rust-lang.org/hello</link>
+      <pubDate>Tue, 14 Nov 2023 22:13:20 +0000</pubDate>
+    </item>
+    <item>
+      <title>No link</title>
+    </item>
+  </channel>
+</rss>"#;
+
+        let feed = parse_rss("suuid-1", text.as_bytes().to_vec())?;
+        assert_eq!(feed.title, "Rust Blog");
+        assert_eq!(feed.feed_format, "RSS");
+        assert!(feed.description.starts_with("Empowering"));
+        assert_eq!(feed.entrys.len(), 1);
+        assert_eq!(feed.entrys[0].suuid, "suuid-1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_posting_frequency() {
+        let dates = vec![
+            "Mon, 01 Jan 2024 00:00:00 +0000".to_string(),
+            "2024-01-05T00:00:00Z".to_string(),
+            "2024-01-11 00:00:00 +00:00".to_string(),
+            "invalid".to_string(),
+        ];
+        assert_eq!(posting_frequency(&dates), Some(0.2));
+
+        assert!(posting_frequency(&dates[..1]).is_none());
+        assert!(posting_frequency(&[dates[0].clone(), dates[0].clone()]).is_none());
+    }
+}
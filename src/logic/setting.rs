@@ -1,40 +1,23 @@
 use super::{
-    entry,
     message::{async_message_success, async_message_warn},
-    rss, ReqData,
+    ReqData,
 };
 use crate::slint_generatedAppWindow::{
     AppWindow, Logic, SettingBackupRecover, SettingProxy, SettingReading, SettingSync,
     SettingUpdate, Store, Theme,
 };
 use crate::{
-    config::{self, Config},
-    db::{self, entry::RssEntry, rss::RssConfig},
-    message_warn,
+    config::{self, BackupRecover},
+    engine, message_warn,
     util::{http, translator::tr},
     version,
 };
 use anyhow::Result;
-use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use chrono::{Local, TimeZone};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 use slint::{ComponentHandle, Weak};
 use std::time::Duration;
-use uuid::Uuid;
-
-const FEEDBACK_URL: &str = "https://heng30.xyz/apisvr/rssbox/android/feedback";
-const BACKUP_URL: &str = "https://heng30.xyz/apisvr/rssbox/android/backup";
-const RECOVER_URL: &str = "https://heng30.xyz/apisvr/rssbox/android/recover";
-const LATEST_VERSION_URL: &str = "https://heng30.xyz/apisvr/latest/version?q=rssbox-android";
-
-// const BACKUP_URL: &str = "http://127.0.0.1:8004/rssbox/android/backup";
-// const RECOVER_URL: &str = "http://127.0.0.1:8004/rssbox/android/recover";
-
-#[derive(Serialize, Deserialize, Default, Debug, Clone)]
-struct BackupRecoverData {
-    rss: Vec<RssConfig>,
-    collection: Vec<RssEntry>,
-    setting: Config,
-}
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 struct SettingUpdateData {
@@ -88,12 +71,12 @@ pub fn init(ui: &AppWindow) {
             .global::<Store>()
             .set_setting_ui(setting.clone());
 
-        let mut all = config::all();
-        all.ui.font_size = font_size.into();
-        all.ui.font_family = setting.font_family.into();
-        all.ui.language = setting.language.into();
-        all.ui.is_dark = setting.is_dark;
-        _ = config::save(all);
+        _ = engine::get().update_settings(|all| {
+            all.ui.font_size = font_size;
+            all.ui.font_family = setting.font_family.into();
+            all.ui.language = setting.language.into();
+            all.ui.is_dark = setting.is_dark;
+        });
     });
 
     ui.global::<Logic>().on_get_setting_sync(move || {
@@ -107,13 +90,12 @@ pub fn init(ui: &AppWindow) {
     });
 
     ui.global::<Logic>().on_set_setting_sync(move |setting| {
-        let mut all = config::all();
-
-        all.sync.sync_interval = setting.sync_interval.parse().unwrap_or(60);
-        all.sync.sync_timeout = setting.sync_timeout.parse().unwrap_or(15);
-        all.sync.is_auto_sync = setting.is_auto_sync;
-        all.sync.is_start_sync = setting.is_start_sync;
-        _ = config::save(all);
+        _ = engine::get().update_settings(|all| {
+            all.sync.sync_interval = setting.sync_interval.parse().unwrap_or(60);
+            all.sync.sync_timeout = setting.sync_timeout.parse().unwrap_or(15);
+            all.sync.is_auto_sync = setting.is_auto_sync;
+            all.sync.is_start_sync = setting.is_start_sync;
+        });
     });
 
     ui.global::<Logic>().on_get_setting_proxy(move || {
@@ -129,13 +111,12 @@ pub fn init(ui: &AppWindow) {
     });
 
     ui.global::<Logic>().on_set_setting_proxy(move |setting| {
-        let mut all = config::all();
-
-        all.proxy.http_url = setting.http_url.into();
-        all.proxy.http_port = setting.http_port.parse().unwrap_or(3218);
-        all.proxy.socks5_url = setting.socks5_url.into();
-        all.proxy.socks5_port = setting.socks5_port.parse().unwrap_or(1080);
-        _ = config::save(all);
+        _ = engine::get().update_settings(|all| {
+            all.proxy.http_url = setting.http_url.into();
+            all.proxy.http_port = setting.http_port.parse().unwrap_or(3218);
+            all.proxy.socks5_url = setting.socks5_url.into();
+            all.proxy.socks5_port = setting.socks5_port.parse().unwrap_or(1080);
+        });
     });
 
     ui.global::<Logic>().on_get_setting_reading(move || {
@@ -148,11 +129,10 @@ pub fn init(ui: &AppWindow) {
     });
 
     ui.global::<Logic>().on_set_setting_reading(move |setting| {
-        let mut all = config::all();
-
-        all.reading.browser = setting.browser.into();
-        all.reading.is_delete_after_reading = setting.is_delete_after_reading;
-        _ = config::save(all);
+        _ = engine::get().update_settings(|all| {
+            all.reading.browser = setting.browser.into();
+            all.reading.is_delete_after_reading = setting.is_delete_after_reading;
+        });
     });
 
     ui.global::<Logic>()
@@ -180,59 +160,87 @@ pub fn init(ui: &AppWindow) {
 
     ui.global::<Logic>().on_get_setting_backup_recover(move || {
         let config = config::backup_recover();
+        let webdav = config::webdav();
+        let status = auto_backup_status(&config);
 
         SettingBackupRecover {
             api_token: config.api_token.into(),
             favorite: config.favorite,
             rss: config.rss,
             setting: config.setting,
+            read_state: config.read_state,
+            blacklist: config.blacklist,
+            trash: config.trash,
+            entrys: config.entrys,
+            remove_rss: config.remove_rss,
+            local_dir: config.local_dir.into(),
+            server_url: config::endpoint().root_url.into(),
+            passphrase: config.passphrase.into(),
+            webdav_url: webdav.url.into(),
+            webdav_username: webdav.username.into(),
+            webdav_password: webdav.password.into(),
+            webdav_use_http_proxy: webdav.use_http_proxy,
+            webdav_use_socks5_proxy: webdav.use_socks5_proxy,
+            auto_backup: config.auto_backup.clone().into(),
+            auto_backup_destination: config.auto_backup_destination.clone().into(),
+            auto_backup_keep: slint::format!("{}", config.auto_backup_keep),
+            auto_backup_status: status.into(),
+            is_auto_backup_failed: !config.last_auto_backup_error.is_empty(),
         }
     });
 
     ui.global::<Logic>()
         .on_set_setting_backup_recover(move |setting| {
-            let mut all = config::all();
-
-            all.backup_recover.api_token = setting.api_token.into();
-            all.backup_recover.favorite = setting.favorite;
-            all.backup_recover.rss = setting.rss;
-            all.backup_recover.setting = setting.setting;
-
-            _ = config::save(all);
+            _ = engine::get().update_settings(|all| {
+                all.backup_recover.api_token = setting.api_token.into();
+                all.backup_recover.favorite = setting.favorite;
+                all.backup_recover.rss = setting.rss;
+                all.backup_recover.setting = setting.setting;
+                all.backup_recover.read_state = setting.read_state;
+                all.backup_recover.blacklist = setting.blacklist;
+                all.backup_recover.trash = setting.trash;
+                all.backup_recover.entrys = setting.entrys;
+                all.backup_recover.remove_rss = setting.remove_rss;
+                all.backup_recover.local_dir = setting.local_dir.trim().into();
+                all.endpoint.root_url = setting.server_url.trim().into();
+                all.backup_recover.passphrase = setting.passphrase.into();
+                all.webdav.url = setting.webdav_url.trim().into();
+                all.webdav.username = setting.webdav_username.trim().into();
+                all.webdav.password = setting.webdav_password.into();
+                all.webdav.use_http_proxy = setting.webdav_use_http_proxy;
+                all.webdav.use_socks5_proxy = setting.webdav_use_socks5_proxy;
+                all.backup_recover.auto_backup = setting.auto_backup.into();
+                all.backup_recover.auto_backup_destination = setting.auto_backup_destination.into();
+                all.backup_recover.auto_backup_keep =
+                    u32::max(1, setting.auto_backup_keep.parse().unwrap_or(7));
+            });
         });
 
-    let ui_handle = ui.as_weak();
-    ui.global::<Logic>().on_backup_to_remote(move |options| {
-        let ui = ui_handle.unwrap();
-        let mut data = BackupRecoverData::default();
-
-        if options.rss {
-            data.rss = rss::get_rss_configs(&ui);
-        }
-
-        if options.favorite {
-            data.collection = entry::get_favorite_entrys(&ui);
-        }
-
-        if options.setting {
-            data.setting = config::all();
-        }
-
-        backup_to_remote(ui.as_weak(), options.api_token.into(), data);
-    });
-
-    let ui_handle = ui.as_weak();
-    ui.global::<Logic>().on_recover_from_remote(move |options| {
-        recover_from_remote(ui_handle.clone(), options);
-    });
-
     let ui_handle = ui.as_weak();
     ui.global::<Logic>().on_get_setting_update(move || {
         _get_setting_update(ui_handle.clone());
     });
 }
 
-fn init_setting(ui: &AppWindow) {
+fn auto_backup_status(config: &BackupRecover) -> String {
+    if !config.last_auto_backup_error.is_empty() {
+        return format!("{}: {}", tr("自动备份失败"), config.last_auto_backup_error);
+    }
+
+    match Local
+        .timestamp_opt(config.last_auto_backup_time, 0)
+        .single()
+    {
+        Some(time) if config.last_auto_backup_time > 0 => format!(
+            "{}: {}",
+            tr("上次自动备份"),
+            time.format("%Y-%m-%d %H:%M:%S")
+        ),
+        _ => String::default(),
+    }
+}
+
+pub fn init_setting(ui: &AppWindow) {
     let config = config::ui();
     let mut ui_setting = ui.global::<Store>().get_setting_ui();
 
@@ -263,7 +271,7 @@ async fn _send_feedback(text: String) -> Result<()> {
     let mut headers = HeaderMap::new();
     headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
     let res = http::client(None)?
-        .post(FEEDBACK_URL)
+        .post(config::endpoint().feedback_url())
         .timeout(Duration::from_secs(15))
         .headers(headers)
         .json(&req)
@@ -280,119 +288,9 @@ async fn _send_feedback(text: String) -> Result<()> {
     Ok(())
 }
 
-fn backup_to_remote(ui: Weak<AppWindow>, api_token: String, data: BackupRecoverData) {
-    tokio::spawn(async move {
-        match _send_backup_to_remove(api_token, data).await {
-            Err(e) => async_message_warn(
-                ui.clone(),
-                format!("{}. {}: {e:?}", tr("备份失败"), tr("原因")),
-            ),
-            _ => async_message_success(ui.clone(), tr("备份成功")),
-        }
-    });
-}
-
-async fn _send_backup_to_remove(api_token: String, data: BackupRecoverData) -> Result<()> {
-    let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
-    headers.insert(
-        AUTHORIZATION,
-        format!("Bearer {api_token}").parse().unwrap(),
-    );
-
-    let url = format!("{BACKUP_URL}?api_token={api_token}");
-    let res = http::client(None)?
-        .post(&url)
-        .timeout(Duration::from_secs(15))
-        .headers(headers)
-        .json(&data)
-        .send()
-        .await?;
-
-    if !res.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "http error code: {}",
-            res.status().as_str()
-        ));
-    }
-
-    Ok(())
-}
-
-async fn _fetch_backup_from_remove(api_token: String) -> Result<BackupRecoverData> {
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        AUTHORIZATION,
-        format!("Bearer {api_token}").parse().unwrap(),
-    );
-
-    let url = format!("{RECOVER_URL}?api_token={api_token}");
-    Ok(http::client(None)?
-        .get(&url)
-        .timeout(Duration::from_secs(15))
-        .headers(headers)
-        .send()
-        .await?
-        .json::<BackupRecoverData>()
-        .await?)
-}
-
-fn recover_from_remote(ui: Weak<AppWindow>, options: SettingBackupRecover) {
-    tokio::spawn(async move {
-        match _fetch_backup_from_remove(options.api_token.into()).await {
-            Ok(data) => {
-                if options.setting {
-                    config::reset(data.setting);
-
-                    let ui = ui.clone();
-                    let _ = slint::invoke_from_event_loop(move || {
-                        init_setting(&ui.unwrap());
-                    });
-                }
-
-                let rss = data.rss;
-                if options.rss {
-                    _ = db::rss::delete_all().await;
-
-                    let ui = ui.clone();
-                    let _ = slint::invoke_from_event_loop(move || {
-                        let ui = ui.unwrap();
-                        rss::remove_all_rss(&ui);
-
-                        for item in rss.into_iter() {
-                            ui.global::<Logic>().invoke_new_rss(item.into());
-                        }
-                    });
-                }
-
-                if options.favorite {
-                    _ = db::entry::delete_all(entry::FAVORITE_UUID).await;
-                    for item in data.collection.into_iter() {
-                        let uuid = Uuid::new_v4().to_string();
-                        if let Ok(text) = serde_json::to_string(&item) {
-                            _ = db::entry::insert(entry::FAVORITE_UUID, &uuid, &text).await;
-                        };
-                    }
-
-                    let ui = ui.clone();
-                    let _ = slint::invoke_from_event_loop(move || {
-                        entry::init_favorite(ui.clone());
-                    });
-                }
-
-                async_message_success(ui.clone(), tr("恢复成功"));
-            }
-            Err(e) => async_message_warn(
-                ui.clone(),
-                format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
-            ),
-        }
-    });
-}
-
 async fn _inner_get_setting_update() -> Result<SettingUpdateData> {
     Ok(http::client(None)?
-        .get(LATEST_VERSION_URL)
+        .get(config::endpoint().latest_version_url())
         .timeout(Duration::from_secs(15))
         .send()
         .await?
@@ -0,0 +1,330 @@
+use super::message::{async_message_success, async_message_warn};
+use crate::slint_generatedAppWindow::{
+    AppWindow, Logic, RssEntry as UIRssEntry, Store, TrashEntry as UITrashEntry, UndoItem,
+};
+use crate::{
+    db::{self, entry::RssEntry, trash::TrashEntry, Repository},
+    message_warn,
+    util::{crypto::md5_hex, translator::tr},
+};
+use anyhow::Result;
+use chrono::{Local, TimeZone};
+use once_cell::sync::Lazy;
+use slint::{ComponentHandle, Model, SharedString, Timer, TimerMode, VecModel};
+use std::{collections::HashSet, future::Future, pin::Pin, sync::Mutex};
+use tokio::sync::mpsc;
+
+const UNDO_INTERVAL: u64 = 5;
+
+// the entrys removed by the latest deletion, which can be restored by the undo action
+static UNDO_ENTRYS: Lazy<Mutex<Vec<(String, RssEntry)>>> = Lazy::new(|| Mutex::new(vec![]));
+
+type Job = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// The removals and the restorations of the entrys run one by one in order,
+// so that an undo never runs before the removal which it restores.
+static JOBS: Lazy<Mutex<Option<mpsc::UnboundedSender<Job>>>> = Lazy::new(|| Mutex::new(None));
+
+#[macro_export]
+macro_rules! store_trash_entrys {
+    ($ui:expr) => {
+        $ui.global::<Store>()
+            .get_trash_entrys()
+            .as_any()
+            .downcast_ref::<VecModel<UITrashEntry>>()
+            .expect("We know we set a VecModel earlier")
+    };
+}
+
+pub fn show_undo(ui: &AppWindow, suuid: &str, entrys: Vec<RssEntry>) {
+    let counts = entrys.len() as i32;
+
+    *UNDO_ENTRYS.lock().unwrap() = entrys
+        .into_iter()
+        .map(|item| (suuid.to_string(), item))
+        .collect();
+
+    ui.global::<Logic>()
+        .invoke_show_undo(slint::format!("{}: {}", tr("已删除"), counts), counts);
+}
+
+pub fn run_in_order(job: impl Future<Output = ()> + Send + 'static) {
+    let jobs = JOBS.lock().unwrap().clone();
+
+    match jobs {
+        Some(jobs) => {
+            if let Err(e) = jobs.send(Box::pin(job)) {
+                tokio::spawn(e.0);
+            }
+        }
+        _ => {
+            tokio::spawn(job);
+        }
+    }
+}
+
+pub fn init(ui: &AppWindow) {
+    store_trash_entrys!(ui).set_vec(vec![]);
+
+    let (jobs, mut rx) = mpsc::unbounded_channel::<Job>();
+    *JOBS.lock().unwrap() = Some(jobs);
+    tokio::spawn(async move {
+        while let Some(job) = rx.recv().await {
+            job.await;
+        }
+    });
+
+    let timer = Timer::default();
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_show_undo(move |text, counts| {
+        let ui = ui_handle.unwrap();
+
+        if timer.running() {
+            timer.stop();
+        }
+
+        ui.global::<Store>().set_undo(UndoItem { text, counts });
+
+        timer.start(
+            TimerMode::SingleShot,
+            std::time::Duration::from_secs(UNDO_INTERVAL),
+            move || {
+                UNDO_ENTRYS.lock().unwrap().clear();
+                ui.global::<Store>().set_undo(UndoItem::default());
+            },
+        );
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_undo_remove_entrys(move || {
+        let ui = ui_handle.unwrap();
+        ui.global::<Store>().set_undo(UndoItem::default());
+
+        let entrys = std::mem::take(&mut *UNDO_ENTRYS.lock().unwrap());
+        if !entrys.is_empty() && restore_entrys(&ui, entrys) == 0 {
+            message_warn!(ui, tr("订阅不存在"));
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_load_trash_entrys(move || {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match db::repo().select_recoverable_trash().await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("加载失败"), tr("原因")),
+                ),
+                Ok(items) => {
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let ui = ui.unwrap();
+                        let entrys = items
+                            .into_iter()
+                            .filter_map(|item| to_ui_trash_entry(&ui, item))
+                            .collect::<Vec<UITrashEntry>>();
+                        store_trash_entrys!(ui).set_vec(entrys);
+                    });
+                }
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_restore_trash_entry(move |md5| {
+        let ui = ui_handle.clone();
+        tokio::spawn(async move {
+            match db::repo().select_trash(&md5).await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+                ),
+                Ok(item) => {
+                    let entry = match serde_json::from_str::<RssEntry>(&item.data) {
+                        Ok(entry) => entry,
+                        Err(e) => {
+                            async_message_warn(
+                                ui.clone(),
+                                format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+                            );
+                            return;
+                        }
+                    };
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let ui = ui.unwrap();
+                        if restore_entrys(&ui, vec![(item.suuid, entry)]) == 0 {
+                            message_warn!(ui, tr("订阅不存在"));
+                            return;
+                        }
+
+                        remove_trash_entry_from_ui(&ui, &md5);
+                    });
+                }
+            }
+        });
+    });
+
+    let ui_handle = ui.as_weak();
+    ui.global::<Logic>().on_remove_trash_entry(move |md5| {
+        let ui = ui_handle.unwrap();
+        remove_trash_entry_from_ui(&ui, &md5);
+
+        let ui = ui.as_weak();
+        tokio::spawn(async move {
+            match db::repo().forget_trash(&md5).await {
+                Err(e) => async_message_warn(
+                    ui.clone(),
+                    format!("{}. {}: {e:?}", tr("删除失败"), tr("原因")),
+                ),
+                _ => async_message_success(ui.clone(), tr("删除成功")),
+            }
+        });
+    });
+}
+
+fn to_ui_trash_entry(ui: &AppWindow, item: TrashEntry) -> Option<UITrashEntry> {
+    let entry = serde_json::from_str::<RssEntry>(&item.data).ok()?;
+
+    let rss_name = match super::rss::get_rss_config(ui, &item.suuid) {
+        Some(rss) => rss.name,
+        _ => tr("订阅不存在").into(),
+    };
+
+    let delete_time = match Local.timestamp_opt(item.delete_time, 0).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        _ => String::default(),
+    };
+
+    Some(UITrashEntry {
+        md5: item.md5.into(),
+        suuid: item.suuid.into(),
+        rss_name,
+        title: entry.title.into(),
+        url: entry.url.into(),
+        delete_time: delete_time.into(),
+    })
+}
+
+fn remove_trash_entry_from_ui(ui: &AppWindow, md5: &SharedString) {
+    for (index, entry) in ui.global::<Store>().get_trash_entrys().iter().enumerate() {
+        if entry.md5 == *md5 {
+            store_trash_entrys!(ui).remove(index);
+            return;
+        }
+    }
+}
+
+// Put the entrys back to their rss in one database transaction, and keep their read states and order.
+// The entrys of the removed rss are skipped. Return the counts of the restored entrys.
+fn restore_entrys(ui: &AppWindow, items: Vec<(String, RssEntry)>) -> usize {
+    let mut restored_entrys = vec![];
+
+    for (index, mut rss) in ui.global::<Store>().get_rss_lists().iter().enumerate() {
+        let mut entrys = items
+            .iter()
+            .filter(|(suuid, _)| rss.uuid == suuid.as_str())
+            .map(|(_, entry)| entry.clone())
+            .collect::<Vec<_>>();
+
+        if entrys.is_empty() {
+            continue;
+        }
+
+        let urls = rss
+            .entry
+            .iter()
+            .map(|item| item.url.to_string())
+            .collect::<HashSet<_>>();
+
+        let model = rss
+            .entry
+            .as_any()
+            .downcast_ref::<VecModel<UIRssEntry>>()
+            .expect("We know we set a VecModel earlier");
+
+        // the entrys are in the order of the list, so insert the last one first
+        for entry in entrys.iter_mut().rev() {
+            entry.suuid = rss.uuid.to_string();
+
+            if urls.contains(&entry.url) {
+                continue;
+            }
+
+            if !entry.is_read {
+                rss.unread_counts += 1;
+            }
+            model.insert(0, entry.clone().into());
+        }
+
+        ui.global::<Store>()
+            .get_rss_lists()
+            .set_row_data(index, rss);
+
+        restored_entrys.extend(entrys);
+    }
+
+    if restored_entrys.is_empty() {
+        return 0;
+    }
+
+    super::rss::notify_ui_update_unread_counts(ui);
+
+    let counts = restored_entrys.len();
+    let ui = ui.as_weak();
+    run_in_order(async move {
+        match _restore_entrys(db::repo().as_ref(), restored_entrys).await {
+            Err(e) => async_message_warn(
+                ui.clone(),
+                format!("{}. {}: {e:?}", tr("恢复失败"), tr("原因")),
+            ),
+            _ => async_message_success(ui.clone(), format!("{}: {counts}", tr("恢复成功"))),
+        }
+    });
+
+    counts
+}
+
+async fn _restore_entrys(repo: &dyn Repository, entrys: Vec<RssEntry>) -> Result<()> {
+    let mut items = vec![];
+    for entry in entrys.into_iter() {
+        items.push(TrashEntry {
+            md5: md5_hex(&entry.url),
+            suuid: entry.suuid.clone(),
+            data: serde_json::to_string(&entry)?,
+            delete_time: 0,
+        });
+    }
+
+    repo.recover_trash(&items).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{EntryRepo, SqliteRepository, TrashRepo};
+
+    #[tokio::test]
+    async fn test_trash_and_restore_entrys() -> Result<()> {
+        let repo = SqliteRepository::memory().await?;
+        repo.new_entry_list("suuid-1").await?;
+
+        let entry = RssEntry {
+            suuid: "suuid-1".to_string(),
+            uuid: "uuid-1".to_string(),
+            url: "https://blog.rust-lang.org/1".to_string(),
+            ..Default::default()
+        };
+
+        super::super::entry::trash_entry(&repo, "suuid-1", &entry).await?;
+        let entrys = super::super::rss::skip_trash_entrys(&repo, vec![entry.clone()]).await;
+        assert!(entrys.is_empty());
+
+        _restore_entrys(&repo, vec![entry.clone()]).await?;
+        assert_eq!(repo.select_all_entrys("suuid-1").await?.len(), 1);
+        assert!(repo.select_recoverable_trash().await?.is_empty());
+
+        let entrys = super::super::rss::skip_trash_entrys(&repo, vec![entry]).await;
+        assert_eq!(entrys.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+extern crate rssbox;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use rssbox::logic::cli;
+use serde::Serialize;
+use std::{fs, io::Read};
+
+/// Command line client which shares the database and the config with the app
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Print the result as json
+    #[arg(short, long, global = true, default_value_t = false)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Subscribe a feed
+    Add {
+        url: String,
+
+        /// Empty means the title of the channel
+        #[arg(short, long, default_value_t = String::default())]
+        name: String,
+
+        /// Category path, such as `Tech/Rust`
+        #[arg(short, long, default_value_t = String::default())]
+        category: String,
+    },
+
+    /// Unsubscribe a feed by the uuid, the prefix of the uuid or the url
+    Remove { id: String },
+
+    /// List the feeds
+    List,
+
+    /// Fetch the new entrys of a feed, or all the feeds if the id is omitted
+    Sync { id: Option<String> },
+
+    /// List the unread entrys of a feed, or all the feeds if the id is omitted
+    Unread { id: Option<String> },
+
+    /// Print an entry and mark it as read by the uuid or the prefix of the uuid
+    Read { id: String },
+
+    /// Export the feeds to an OPML file, `-` means the stdout
+    ExportOpml {
+        #[arg(default_value_t = String::from("-"))]
+        path: String,
+    },
+
+    /// Import the feeds from an OPML file, `-` means the stdin
+    ImportOpml { path: String },
+}
+
+#[cfg(not(target_os = "android"))]
+#[tokio::main]
+async fn main() -> Result<()> {
+    rssbox::init_logger();
+
+    let args = Args::parse();
+    cli::init().await;
+
+    match args.command {
+        Command::Add {
+            url,
+            name,
+            category,
+        } => {
+            let feed = cli::add(&url, &name, &category).await?;
+            output(args.json, &feed, || {
+                println!("added: {} {} {}", feed.uuid, feed.name, feed.url)
+            })
+        }
+        Command::Remove { id } => {
+            let feed = cli::remove(&id).await?;
+            output(args.json, &feed, || {
+                println!("removed: {} {} {}", feed.uuid, feed.name, feed.url)
+            })
+        }
+        Command::List => {
+            let feeds = cli::list().await?;
+            output(args.json, &feeds, || {
+                for feed in feeds.iter() {
+                    println!(
+                        "{} [{}/{}] {} {}",
+                        feed.uuid, feed.unread_counts, feed.total_counts, feed.name, feed.url
+                    );
+                }
+            })
+        }
+        Command::Sync { id } => {
+            let results = cli::sync(id.as_deref()).await?;
+            output(args.json, &results, || {
+                for item in results.iter() {
+                    if item.error.is_empty() {
+                        println!("{} {}: {} new", item.uuid, item.name, item.new_counts);
+                    } else {
+                        println!("{} {}: failed, {}", item.uuid, item.name, item.error);
+                    }
+                }
+            })
+        }
+        Command::Unread { id } => {
+            let entrys = cli::unread(id.as_deref()).await?;
+            output(args.json, &entrys, || {
+                for entry in entrys.iter() {
+                    println!(
+                        "{} [{}] {} {}",
+                        entry.uuid, entry.feed_name, entry.title, entry.url
+                    );
+                }
+            })
+        }
+        Command::Read { id } => {
+            let entry = cli::read(&id).await?;
+            output(args.json, &entry, || {
+                println!(
+                    "{}\n{}\n{} {}",
+                    entry.title, entry.url, entry.author, entry.pub_date
+                );
+                if !entry.summary.is_empty() {
+                    println!("\n{}", entry.summary);
+                }
+            })
+        }
+        Command::ExportOpml { path } => {
+            let text = cli::export_opml().await?;
+            if path == "-" {
+                print!("{text}");
+            } else {
+                fs::write(&path, text).with_context(|| format!("write {path} failed"))?;
+            }
+            Ok(())
+        }
+        Command::ImportOpml { path } => {
+            let text = if path == "-" {
+                let mut text = String::default();
+                std::io::stdin().read_to_string(&mut text)?;
+                text
+            } else {
+                fs::read_to_string(&path).with_context(|| format!("read {path} failed"))?
+            };
+
+            let counts = cli::import_opml(&text).await?;
+            output(args.json, &counts, || println!("imported: {counts}"))
+        }
+    }
+}
+
+fn output<T: Serialize>(is_json: bool, value: &T, print_text: impl FnOnce()) -> Result<()> {
+    if is_json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        print_text();
+    }
+    Ok(())
+}
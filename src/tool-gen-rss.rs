@@ -2,21 +2,31 @@ extern crate rssbox;
 
 use anyhow::{Context, Result};
 use atom_syndication::Feed;
+use chrono::{Local, Utc};
 use clap::Parser;
-use opml::OPML;
 use reqwest::header::{HeaderMap, CONTENT_TYPE};
 use rss::Channel;
 use rssbox::{
     db::ComEntry,
-    logic::{rss_valid, FindEntry},
-    util::http,
+    logic::{parse_opml as parse_opml_entrys, rss_valid, FindEntry},
+    util::{http, time, url},
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::BufReader,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    fs::File,
+    io::AsyncWriteExt,
+    sync::{mpsc, Semaphore},
 };
-use std::{collections::HashSet, fs, io::BufReader, time::Duration};
-use tokio::{fs::File, io::AsyncWriteExt, sync::mpsc};
 
-const RSS_CN_OPML: &str = include_str!("../data/rss-cn.opml");
-const RSS_EN_OPML: &str = include_str!("../data/rss-en.opml");
-const RSS_CN: &str = include_str!("../data/rss-cn.json");
+const RSS_CN_INPUTS: [&str; 2] = ["./data/rss-cn.json", "./data/rss-cn.opml"];
+const RSS_EN_INPUTS: [&str; 1] = ["./data/rss-en.opml"];
 const RSS_CN_VALID_PATH: &str = "./data/rss-valid-cn.json";
 const RSS_EN_VALID_PATH: &str = "./data/rss-valid-en.json";
 
@@ -28,13 +38,74 @@ struct Args {
     #[arg(short, long, default_value_t = false)]
     generate: bool,
 
-    /// Send chinese rss to remote
+    /// Generate or send chinese rss
     #[arg(short, long, default_value_t = false)]
     is_cn: bool,
 
     /// API server root url
     #[arg(short, long, default_value_t = String::default())]
     root_url: String,
+
+    /// Input files: `.opml`, `.xml`, `.json` or a plain url list with one url per line.
+    /// Empty means the files in `./data` of both the chinese and the english rss
+    #[arg(short = 'f', long, num_args = 1..)]
+    inputs: Vec<String>,
+
+    /// Output file of the valid rss. Empty means `./data/rss-valid-<cn|en>.json`
+    #[arg(short, long, default_value_t = String::default())]
+    output: String,
+
+    /// Max counts of the rss which are fetched at the same time
+    #[arg(long, default_value_t = 32)]
+    concurrency: usize,
+
+    /// Retry times of a failed request
+    #[arg(long, default_value_t = 2)]
+    retries: u32,
+
+    /// Drop the rss which has not published in the days, 0 means no limit
+    #[arg(long, default_value_t = 0)]
+    min_freshness: i64,
+
+    /// Print what would be added, updated or removed on the api server without sending
+    #[arg(long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Remove the rss which are not in the valid rss file from the api server
+    #[arg(long, default_value_t = false)]
+    remove_dead: bool,
+
+    /// API token which is required to add and remove the rss in the api server
+    #[arg(long, default_value_t = String::default())]
+    api_token: String,
+}
+
+impl Args {
+    fn language(&self) -> &'static str {
+        if self.is_cn {
+            "cn"
+        } else {
+            "en"
+        }
+    }
+
+    fn valid_path(&self, language: &str) -> String {
+        if !self.output.is_empty() {
+            self.output.clone()
+        } else if language == "cn" {
+            RSS_CN_VALID_PATH.to_string()
+        } else {
+            RSS_EN_VALID_PATH.to_string()
+        }
+    }
+}
+
+// The rss which is dropped because of a duplicated url
+#[derive(Serialize, Debug, Clone)]
+struct Conflict {
+    normalized_url: String,
+    kept: FindEntry,
+    dropped: FindEntry,
 }
 
 #[cfg(not(target_os = "android"))]
@@ -44,13 +115,13 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
     if args.generate {
-        generate_valid_rss()
+        generate_valid_rss(&args)
             .await
             .context("generate valid rss failed")?;
     }
 
     if !args.root_url.is_empty() {
-        update_apisvr_rss(&args.root_url, args.is_cn)
+        update_apisvr_rss(&args)
             .await
             .context("update apisvr rss failed")?;
     }
@@ -58,13 +129,15 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn update_apisvr_rss(root_url: &str, is_cn: bool) -> Result<()> {
+fn rss_list_url(root_url: &str, language: &str) -> String {
+    format!("{root_url}/rssbox/rss/list/{language}")
+}
+
+async fn update_apisvr_rss(args: &Args) -> Result<()> {
     log::info!("update start...");
 
-    let get_url = format!(
-        "{root_url}/rssbox/rss/list/{}",
-        if is_cn { "cn" } else { "en" }
-    );
+    let (root_url, language) = (args.root_url.trim_end_matches('/'), args.language());
+    let get_url = rss_list_url(root_url, language);
 
     log::info!("get url: {}", get_url);
 
@@ -77,64 +150,97 @@ async fn update_apisvr_rss(root_url: &str, is_cn: bool) -> Result<()> {
         .await?
         .into_iter()
         .filter_map(|item| serde_json::from_str::<FindEntry>(&item.data).ok())
-        .collect::<HashSet<_>>();
+        .collect::<Vec<_>>();
 
     log::info!("remote_items: {}", remote_items.len());
 
-    let json_text = fs::read_to_string(if is_cn {
-        RSS_CN_VALID_PATH
-    } else {
-        RSS_EN_VALID_PATH
-    })
-    .context("read valid json file failed")?;
+    let json_text =
+        fs::read_to_string(args.valid_path(language)).context("read valid json file failed")?;
 
-    let local_items = rss_valid(&json_text)
-        .context("parse json file error")?
-        .into_iter()
-        .collect::<HashSet<_>>();
+    let local_items = rss_valid(&json_text).context("parse json file error")?;
 
     log::info!("local_items: {}", local_items.len());
 
-    let difference_items: Vec<_> = local_items.difference(&remote_items).cloned().collect();
+    let (added, updated, removed) = diff_rss(&local_items, &remote_items);
+    let removed = if args.remove_dead { removed } else { vec![] };
 
-    let difference_items_len = difference_items.len();
-    log::info!("difference_items: {}", difference_items_len);
+    log::info!(
+        "added: {}, updated: {}, removed: {}",
+        added.len(),
+        updated.len(),
+        removed.len()
+    );
 
-    if difference_items_len == 0 {
+    if args.dry_run {
+        for (flag, items) in [("+", &added), ("~", &updated), ("-", &removed)] {
+            for item in items.iter() {
+                println!("{flag} {} {}", item.name, item.url);
+            }
+        }
         return Ok(());
     }
 
-    let (tx, mut rx) = mpsc::channel(difference_items_len);
+    if args.api_token.trim().is_empty() {
+        anyhow::bail!("the api token is required to update the api server");
+    }
+
+    let semaphore = Arc::new(Semaphore::new(args.concurrency.max(1)));
+    let (tx, mut rx) = mpsc::channel(added.len() + updated.len() + removed.len() + 1);
 
-    for item in difference_items.into_iter() {
-        let (root_url, tx) = (root_url.to_string(), tx.clone());
+    // the api server updates the existing entry, so the updated entrys are sent as the new ones
+    for item in added.into_iter().chain(updated) {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let (post_url, tx) = (rss_list_url(root_url, language), tx.clone());
+        let api_token = args.api_token.clone();
 
         tokio::spawn(async move {
-            let post_url = format!(
-                "{root_url}/rssbox/rss/list/{}",
-                if is_cn { "cn" } else { "en" }
-            );
             let mut headers = HeaderMap::new();
             headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
 
-            _ = http::client(None)
+            let res = http::client(None)
                 .expect("create http client failed")
                 .post(post_url)
                 .timeout(Duration::from_secs(30))
                 .headers(headers)
+                .bearer_auth(api_token)
                 .json(&item)
                 .send()
                 .await;
 
-            _ = tx.send(()).await;
+            _ = tx.send(("+", item.url, res.map(|res| res.status()))).await;
+            drop(permit);
+        });
+    }
+
+    for item in removed.into_iter() {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let (delete_url, tx) = (rss_list_url(root_url, language), tx.clone());
+        let api_token = args.api_token.clone();
+
+        tokio::spawn(async move {
+            let res = http::client(None)
+                .expect("create http client failed")
+                .delete(delete_url)
+                .query(&[("url", &item.url)])
+                .bearer_auth(api_token)
+                .timeout(Duration::from_secs(30))
+                .send()
+                .await;
+
+            _ = tx.send(("-", item.url, res.map(|res| res.status()))).await;
+            drop(permit);
         });
     }
 
     drop(tx);
 
     let mut count = 1;
-    while let Some(_) = rx.recv().await {
-        log::info!("{count}/{difference_items_len}");
+    while let Some((flag, url, res)) = rx.recv().await {
+        match res {
+            Ok(status) if status.is_success() => log::info!("{count}: {flag} {url}"),
+            Ok(status) => log::warn!("{count}: {flag} {url}, http error code: {status}"),
+            Err(e) => log::warn!("{count}: {flag} {url}, {e}"),
+        }
         count += 1;
     }
 
@@ -143,92 +249,259 @@ async fn update_apisvr_rss(root_url: &str, is_cn: bool) -> Result<()> {
     Ok(())
 }
 
-async fn generate_valid_rss() -> Result<()> {
-    _ = generate_valid_rss_cn()
-        .await
-        .context("generate valid rss cn failed")?;
+// Compare the rss by the normalized urls, return the added, the updated and the removed rss
+fn diff_rss(
+    local_items: &[FindEntry],
+    remote_items: &[FindEntry],
+) -> (Vec<FindEntry>, Vec<FindEntry>, Vec<FindEntry>) {
+    let remote = remote_items
+        .iter()
+        .map(|item| (url::normalize(&item.url), item))
+        .collect::<HashMap<_, _>>();
+
+    let local = local_items
+        .iter()
+        .map(|item| url::normalize(&item.url))
+        .collect::<HashSet<_>>();
+
+    let (mut added, mut updated) = (vec![], vec![]);
+    for item in local_items.iter() {
+        match remote.get(&url::normalize(&item.url)) {
+            None => added.push(item.clone()),
+            Some(remote_item) => {
+                if serde_json::to_value(item).ok() != serde_json::to_value(remote_item).ok() {
+                    updated.push(item.clone());
+                }
+            }
+        }
+    }
 
-    _ = generate_valid_rss_en()
-        .await
-        .context("generate valid rss en failed")?;
+    let removed = remote_items
+        .iter()
+        .filter(|item| !local.contains(&url::normalize(&item.url)))
+        .cloned()
+        .collect();
 
-    Ok(())
+    (added, updated, removed)
 }
 
-async fn generate_valid_rss_cn() -> Result<()> {
-    log::info!("generate rss cn start...");
+async fn generate_valid_rss(args: &Args) -> Result<()> {
+    let languages = if args.inputs.is_empty() {
+        vec!["cn", "en"]
+    } else {
+        vec![args.language()]
+    };
+
+    for language in languages.into_iter() {
+        let inputs = if !args.inputs.is_empty() {
+            args.inputs.clone()
+        } else if language == "cn" {
+            RSS_CN_INPUTS.iter().map(|item| item.to_string()).collect()
+        } else {
+            RSS_EN_INPUTS.iter().map(|item| item.to_string()).collect()
+        };
+
+        generate_valid_rss_language(args, language, &inputs)
+            .await
+            .with_context(|| format!("generate valid rss {language} failed"))?;
+    }
 
-    let _assert = File::create(RSS_CN_VALID_PATH).await?;
+    Ok(())
+}
 
-    let json_items = rss_valid(RSS_CN).context("parse json file error")?;
-    log::info!("{}", json_items.len());
-    assert!(!json_items.is_empty());
+async fn generate_valid_rss_language(args: &Args, language: &str, inputs: &[String]) -> Result<()> {
+    log::info!("generate rss {language} start...");
 
-    let opml_items = parse_opml(RSS_CN_OPML).context("parse opml cn file error")?;
-    log::info!("{}", opml_items.len());
-    assert!(!opml_items.is_empty());
+    let save_path = args.valid_path(language);
+    let _assert = File::create(&save_path).await?;
 
-    let items = json_items
-        .into_iter()
-        .chain(opml_items)
-        .into_iter()
-        .collect::<Vec<_>>();
+    let mut items = vec![];
+    for input in inputs.iter() {
+        let input_items =
+            load_input(input, language).with_context(|| format!("parse {input} error"))?;
+        log::info!("{input}: {}", input_items.len());
+        items.extend(input_items);
+    }
 
-    log::info!("{}", items.len());
+    let (items, conflicts) = dedup_rss(items);
+    log::info!("{}, conflicts: {}", items.len(), conflicts.len());
     assert!(!items.is_empty());
 
-    _ = save_valid_rss(items, RSS_CN_VALID_PATH).await;
-    log::info!("generate rss cn exit...");
+    save_conflicts(&conflicts, &save_path).await?;
+    _ = save_valid_rss(items, &save_path, args).await;
+
+    log::info!("generate rss {language} exit...");
 
     Ok(())
 }
 
-async fn generate_valid_rss_en() -> Result<()> {
-    log::info!("generate rss en start...");
+// The format is detected by the extension of the file
+fn load_input(path: &str, language: &str) -> Result<Vec<FindEntry>> {
+    let text = fs::read_to_string(path)?;
+    let lower_path = path.to_lowercase();
+
+    let items = if lower_path.ends_with(".opml") || lower_path.ends_with(".xml") {
+        parse_opml(&text, language)?
+    } else if lower_path.ends_with(".json") {
+        rss_valid(&text)?
+    } else {
+        parse_url_list(&text)
+    };
+
+    Ok(items
+        .into_iter()
+        .map(|mut item| {
+            if item.language.is_empty() {
+                item.language = language.to_string();
+            }
+            item
+        })
+        .collect())
+}
 
-    let _assert = File::create(RSS_EN_VALID_PATH).await?;
+// One url per line, and the lines starting with `#` are comments.
+// The name is filled with the title of the channel when the rss is fetched.
+fn parse_url_list(text: &str) -> Vec<FindEntry> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| line.starts_with("http"))
+        .map(|line| FindEntry {
+            url: line.to_string(),
+            ..Default::default()
+        })
+        .collect()
+}
 
-    let opml_items = parse_opml(RSS_EN_OPML).context("parse opml en file error")?;
-    log::info!("{}", opml_items.len());
-    assert!(!opml_items.is_empty());
+// The first rss of the same normalized url is kept
+fn dedup_rss(items: Vec<FindEntry>) -> (Vec<FindEntry>, Vec<Conflict>) {
+    let mut kept_items: Vec<FindEntry> = vec![];
+    let mut indexs: HashMap<String, usize> = HashMap::new();
+    let mut conflicts = vec![];
+
+    for item in items.into_iter() {
+        let normalized_url = url::normalize(&item.url);
+
+        match indexs.get(&normalized_url) {
+            Some(index) => conflicts.push(Conflict {
+                normalized_url,
+                kept: kept_items[*index].clone(),
+                dropped: item,
+            }),
+            _ => {
+                indexs.insert(normalized_url, kept_items.len());
+                kept_items.push(item);
+            }
+        }
+    }
 
-    _ = save_valid_rss(opml_items, RSS_EN_VALID_PATH).await;
+    (kept_items, conflicts)
+}
 
-    log::info!("generate rss en exit...");
+async fn save_conflicts(conflicts: &[Conflict], save_path: &str) -> Result<()> {
+    let path = format!("{}.conflicts.json", save_path.trim_end_matches(".json"));
+    let mut file = File::create(&path).await?;
+    file.write_all(serde_json::to_string_pretty(conflicts)?.as_bytes())
+        .await?;
+
+    for item in conflicts.iter() {
+        log::info!(
+            "conflict: {}, kept: {}, dropped: {}",
+            item.normalized_url,
+            item.kept.name,
+            item.dropped.name
+        );
+    }
 
     Ok(())
 }
 
-async fn save_valid_rss(items: Vec<FindEntry>, save_path: &str) -> Result<()> {
+#[derive(Serialize, Debug, Clone, Default)]
+struct ValidReport {
+    name: String,
+    url: String,
+    final_url: String,
+
+    // 0 means the request failed before any response
+    status: u16,
+    latency_ms: u64,
+    attempts: u32,
+
+    // `RSS`, `ATOM` or empty if the content can't be parsed
+    format: String,
+    entrys: usize,
+
+    // rfc3339, empty if no entry has a date
+    newest_date: String,
+    is_valid: bool,
+    error: String,
+}
+
+impl ValidReport {
+    const CSV_HEADER: &'static str =
+        "name,url,final_url,status,latency_ms,attempts,format,entrys,newest_date,is_valid,error";
+
+    fn to_csv(&self) -> String {
+        [
+            csv_field(&self.name),
+            csv_field(&self.url),
+            csv_field(&self.final_url),
+            self.status.to_string(),
+            self.latency_ms.to_string(),
+            self.attempts.to_string(),
+            self.format.clone(),
+            self.entrys.to_string(),
+            self.newest_date.clone(),
+            self.is_valid.to_string(),
+            csv_field(&self.error),
+        ]
+        .join(",")
+    }
+}
+
+struct FetchResponse {
+    status: u16,
+    final_url: String,
+    content: Vec<u8>,
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+async fn save_valid_rss(items: Vec<FindEntry>, save_path: &str, args: &Args) -> Result<()> {
     let total_len = items.len();
-    let (tx, mut rx) = mpsc::channel(total_len);
+    let (tx, mut rx) = mpsc::channel(total_len.max(1));
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let semaphore = Arc::new(Semaphore::new(args.concurrency.max(1)));
+
+    for (index, mut item) in items.into_iter().enumerate() {
+        // only `concurrency` tasks are running at the same time
+        let permit = semaphore.clone().acquire_owned().await?;
+        let (tx, today) = (tx.clone(), today.clone());
+        let (retries, min_freshness) = (args.retries, args.min_freshness);
 
-    for (index, item) in items.into_iter().enumerate() {
-        let tx = tx.clone();
         tokio::spawn(async move {
-            match fetch_rss(&item.url).await {
-                Ok(content) => {
-                    if let Ok(channel) = Channel::read_from(&content[..]) {
-                        if !channel.items.is_empty() {
-                            _ = tx.send(item).await;
-                        }
-                    } else if let Ok(feed) = Feed::read_from(BufReader::new(&content[..])) {
-                        if !feed.entries.is_empty() {
-                            _ = tx.send(item).await;
-                        }
-                    }
-                }
-                _ => (),
-            }
+            item.last_verified = today;
+            let report = check_rss(&mut item, retries, min_freshness).await;
             log::info!("{}/{total_len}", index + 1);
+
+            _ = tx.send((item, report)).await;
+            drop(permit);
         });
     }
 
     drop(tx);
 
-    let mut valid_items = HashSet::new();
-    while let Some(item) = rx.recv().await {
-        valid_items.insert(item);
+    let (mut valid_items, mut reports) = (HashSet::new(), vec![]);
+    while let Some((item, report)) = rx.recv().await {
+        if report.is_valid {
+            valid_items.insert(item);
+        }
+        reports.push(report);
     }
     let valid_items = valid_items.into_iter().collect::<Vec<_>>();
 
@@ -239,30 +512,182 @@ async fn save_valid_rss(items: Vec<FindEntry>, save_path: &str) -> Result<()> {
     let mut file = File::create(save_path).await?;
     file.write_all(text.as_bytes()).await?;
 
+    save_report(reports, save_path).await
+}
+
+// The reports are saved next to the valid rss file, such as `rss-valid-cn.report.json`
+async fn save_report(mut reports: Vec<ValidReport>, save_path: &str) -> Result<()> {
+    reports.sort_by(|a, b| a.url.cmp(&b.url));
+
+    let path = save_path.trim_end_matches(".json");
+    let mut file = File::create(format!("{path}.report.json")).await?;
+    file.write_all(serde_json::to_string_pretty(&reports)?.as_bytes())
+        .await?;
+
+    let text = std::iter::once(ValidReport::CSV_HEADER.to_string())
+        .chain(reports.iter().map(|item| item.to_csv()))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut file = File::create(format!("{path}.report.csv")).await?;
+    file.write_all(text.as_bytes()).await?;
+
+    log::info!(
+        "report: {path}.report.json, {path}.report.csv, invalid items: {}",
+        reports.iter().filter(|item| !item.is_valid).count()
+    );
+
     Ok(())
 }
 
-async fn fetch_rss(url: &str) -> Result<Vec<u8>> {
-    Ok(http::client(None)?
+// Fetch and parse the feed, and fill the missing description and site url of the item
+async fn check_rss(item: &mut FindEntry, retries: u32, min_freshness: i64) -> ValidReport {
+    let mut report = ValidReport {
+        name: item.name.clone(),
+        url: item.url.clone(),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    let res = fetch_rss(&item.url, retries, &mut report.attempts).await;
+    report.latency_ms = start.elapsed().as_millis() as u64;
+
+    let res = match res {
+        Ok(v) => v,
+        Err(e) => {
+            report.error = e.to_string();
+            return report;
+        }
+    };
+
+    report.status = res.status;
+    report.final_url = res.final_url;
+    if !(200..300).contains(&res.status) {
+        report.error = format!("http error code: {}", res.status);
+        return report;
+    }
+
+    let dates = if let Ok(channel) = Channel::read_from(&res.content[..]) {
+        report.format = "RSS".to_string();
+        report.entrys = channel.items.len();
+
+        if item.name.is_empty() {
+            item.name = channel.title.trim().to_string();
+        }
+        if item.description.is_empty() {
+            item.description = channel.description.trim().to_string();
+        }
+        if item.site_url.is_empty() {
+            item.site_url = channel.link.trim().to_string();
+        }
+
+        channel
+            .items
+            .iter()
+            .filter_map(|entry| entry.pub_date.as_deref().and_then(time::parse_pub_date))
+            .collect::<Vec<_>>()
+    } else if let Ok(feed) = Feed::read_from(BufReader::new(&res.content[..])) {
+        report.format = "ATOM".to_string();
+        report.entrys = feed.entries.len();
+
+        if item.name.is_empty() {
+            item.name = feed.title.value.trim().to_string();
+        }
+        if item.description.is_empty() {
+            item.description = feed
+                .subtitle
+                .as_ref()
+                .map(|text| text.value.trim().to_string())
+                .unwrap_or_default();
+        }
+        if item.site_url.is_empty() {
+            item.site_url = feed
+                .links
+                .iter()
+                .find(|link| link.rel == "alternate")
+                .map(|link| link.href.clone())
+                .unwrap_or_default();
+        }
+
+        feed.entries
+            .iter()
+            .map(|entry| entry.published.unwrap_or(entry.updated))
+            .collect::<Vec<_>>()
+    } else {
+        report.error = "invalid rss or atom content".to_string();
+        return report;
+    };
+
+    let newest_date = dates.into_iter().max();
+    report.newest_date = newest_date
+        .map(|date| date.to_rfc3339())
+        .unwrap_or_default();
+
+    report.name = item.name.clone();
+
+    if report.entrys == 0 {
+        report.error = "no entrys".to_string();
+    } else if item.name.is_empty() {
+        report.error = "no name".to_string();
+    } else if min_freshness > 0
+        && newest_date.is_none_or(|date| {
+            Utc::now().signed_duration_since(date) > chrono::Duration::days(min_freshness)
+        })
+    {
+        report.error = format!("no entrys in {min_freshness} days");
+    } else {
+        report.is_valid = true;
+    }
+
+    report
+}
+
+// Retry on the network errors, the server errors and the rate limits
+async fn fetch_rss(url: &str, retries: u32, attempts: &mut u32) -> Result<FetchResponse> {
+    loop {
+        *attempts += 1;
+
+        match fetch_rss_once(url).await {
+            Ok(res) if res.status < 500 && res.status != 429 => return Ok(res),
+            Ok(res) if *attempts > retries => return Ok(res),
+            Err(e) if *attempts > retries => return Err(e),
+            _ => tokio::time::sleep(Duration::from_secs(*attempts as u64)).await,
+        }
+    }
+}
+
+async fn fetch_rss_once(url: &str) -> Result<FetchResponse> {
+    let res = http::client(None)?
         .get(url)
         .headers(http::headers())
         .timeout(Duration::from_secs(30))
         .send()
-        .await?
-        .bytes()
-        .await?
-        .to_vec())
+        .await?;
+
+    Ok(FetchResponse {
+        status: res.status().as_u16(),
+        final_url: res.url().to_string(),
+        content: res.bytes().await?.to_vec(),
+    })
 }
 
-fn parse_opml(text: &str) -> Result<Vec<FindEntry>> {
-    Ok(OPML::from_str(text)?
-        .body
-        .outlines
+// The folders of the OPML are kept as the categories
+fn parse_opml(text: &str, language: &str) -> Result<Vec<FindEntry>> {
+    Ok(parse_opml_entrys(text)?
         .into_iter()
-        .filter(|item| !item.text.is_empty() && item.xml_url.is_some())
+        .filter(|item| !item.name.is_empty())
         .map(|item| FindEntry {
-            name: item.text,
-            url: item.xml_url.unwrap(),
+            name: item.name,
+            url: item.url,
+            category: item.category,
+            description: item.description,
+            language: language.to_string(),
+            site_url: if item.site_url.starts_with("http") {
+                item.site_url
+            } else {
+                String::default()
+            },
+            ..Default::default()
         })
         .collect())
 }
@@ -1,5 +1,93 @@
+use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    XChaCha20Poly1305, XNonce,
+};
 use md5;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
 
 pub fn md5_hex(text: &str) -> String {
     format!("{:X}", md5::compute(text))
 }
+
+// The key is derived from the passphrase by Argon2id, and the data is encrypted by XChaCha20-Poly1305.
+// All the binary fields are base64 encoded.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptedData {
+    pub kdf: String,
+    pub cipher: String,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("derive key failed: {e}"))?;
+    Ok(key)
+}
+
+pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<EncryptedData> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt)?;
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|e| anyhow!("encrypt failed: {e}"))?;
+
+    Ok(EncryptedData {
+        kdf: "argon2id".to_string(),
+        cipher: "xchacha20poly1305".to_string(),
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+// The authentication fails if the passphrase is wrong or the data is modified,
+// they can't be distinguished.
+pub fn decrypt(passphrase: &str, data: &EncryptedData) -> Result<Vec<u8>> {
+    if data.kdf != "argon2id" || data.cipher != "xchacha20poly1305" {
+        anyhow::bail!("unsupported encryption: {} {}", data.kdf, data.cipher);
+    }
+
+    let salt = STANDARD.decode(&data.salt)?;
+    let nonce = STANDARD.decode(&data.nonce)?;
+    let ciphertext = STANDARD.decode(&data.ciphertext)?;
+    if nonce.len() != NONCE_LEN {
+        anyhow::bail!("invalid nonce length: {}", nonce.len());
+    }
+
+    let key = derive_key(passphrase, &salt)?;
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("wrong passphrase or corrupted data"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() -> Result<()> {
+        let data = encrypt("passphrase-1", b"hello rssbox")?;
+        assert_eq!(decrypt("passphrase-1", &data)?, b"hello rssbox");
+        assert!(decrypt("passphrase-2", &data).is_err());
+
+        let data_2 = encrypt("passphrase-1", b"hello rssbox")?;
+        assert_ne!(data.ciphertext, data_2.ciphertext);
+        Ok(())
+    }
+}
@@ -2,6 +2,10 @@ pub mod crypto;
 pub mod fs;
 pub mod http;
 pub mod number;
+pub mod recommend;
+pub mod search;
 pub mod str;
 pub mod time;
 pub mod translator;
+pub mod url;
+pub mod webdav;
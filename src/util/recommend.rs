@@ -0,0 +1,253 @@
+use reqwest::Url;
+use std::collections::{HashMap, HashSet};
+
+const SCORE_DOMAIN: f64 = 3.0;
+const SCORE_CATEGORY: f64 = 2.0;
+const FAVORITE_WEIGHT: f64 = 2.0;
+const MIN_WORD_LEN: usize = 3;
+
+const STOP_WORDS: [&str; 24] = [
+    "the", "and", "for", "with", "from", "that", "this", "are", "was", "you", "your", "how",
+    "what", "why", "new", "not", "but", "all", "can", "about", "into", "has", "have", "its",
+];
+
+// A feed which the user follows. The terms come from the titles and the tags of its entrys.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    pub name: String,
+    pub url: String,
+    pub category: String,
+    terms: HashMap<String, f64>,
+    weight: f64,
+}
+
+// A feed in the catalog which can be recommended
+#[derive(Debug, Clone, Default)]
+pub struct Candidate {
+    pub name: String,
+    pub url: String,
+    pub category: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    // the index of the candidate
+    pub index: usize,
+
+    // the index of the profile which contributes the most, it's the reason of the recommendation
+    pub profile: usize,
+    pub score: f64,
+}
+
+impl Profile {
+    pub fn new(name: &str, url: &str, category: &str, is_favorite: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            category: category.to_string(),
+            terms: HashMap::new(),
+            weight: if is_favorite { FAVORITE_WEIGHT } else { 1.0 },
+        }
+    }
+
+    // The favorite entrys count more than the other entrys
+    pub fn add_text(&mut self, text: &str, is_favorite: bool) {
+        let weight = if is_favorite { FAVORITE_WEIGHT } else { 1.0 };
+        for term in terms(text) {
+            *self.terms.entry(term).or_default() += weight;
+        }
+    }
+}
+
+// The lowercase words of the text. The Chinese text has no spaces, so its bigrams are used.
+pub fn terms(text: &str) -> HashSet<String> {
+    let mut items = HashSet::new();
+    let (mut word, mut cjk) = (String::default(), vec![]);
+
+    for c in text.chars().chain(std::iter::once(' ')) {
+        if is_cjk(c) {
+            cjk.push(c);
+        } else {
+            for pair in cjk.windows(2) {
+                items.insert(pair.iter().collect::<String>());
+            }
+            cjk.clear();
+        }
+
+        if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+        } else {
+            if word.len() >= MIN_WORD_LEN && !STOP_WORDS.contains(&word.as_str()) {
+                items.insert(word.clone());
+            }
+            word.clear();
+        }
+    }
+
+    items
+}
+
+fn is_cjk(c: char) -> bool {
+    ('\u{4e00}'..='\u{9fff}').contains(&c)
+}
+
+// The host without `www.`. The full host is used, because the top 2 levels
+// are shared by the unrelated sites, such as `github.io` and `co.uk`.
+pub fn domain(url: &str) -> String {
+    let Ok(u) = Url::parse(url.trim()) else {
+        return String::default();
+    };
+
+    u.host_str()
+        .unwrap_or_default()
+        .trim_start_matches("www.")
+        .to_string()
+}
+
+fn top_category(category: &str) -> String {
+    category
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+fn score(profile: &Profile, candidate: &Candidate, candidate_terms: &HashSet<String>) -> f64 {
+    let mut score = candidate_terms
+        .iter()
+        .filter_map(|term| profile.terms.get(term))
+        .map(|counts| (1.0 + counts).ln())
+        .sum::<f64>();
+
+    let candidate_domain = domain(&candidate.url);
+    if !candidate_domain.is_empty() && candidate_domain == domain(&profile.url) {
+        score += SCORE_DOMAIN;
+    }
+
+    let category = top_category(&candidate.category);
+    if !category.is_empty() && category == top_category(&profile.category) {
+        score += SCORE_CATEGORY;
+    }
+
+    score * profile.weight
+}
+
+// Return the most relevant candidates, and the best one is the first
+pub fn recommend(
+    profiles: &[Profile],
+    candidates: &[Candidate],
+    max_counts: usize,
+) -> Vec<Recommendation> {
+    let mut items = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, candidate)| {
+            let candidate_terms = terms(&format!(
+                "{} {} {}",
+                candidate.name, candidate.description, candidate.category
+            ));
+
+            let scores = profiles
+                .iter()
+                .map(|profile| score(profile, candidate, &candidate_terms))
+                .collect::<Vec<_>>();
+
+            let (profile, best) = scores
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))?;
+
+            if *best <= 0.0 {
+                return None;
+            }
+
+            Some(Recommendation {
+                index,
+                profile,
+                score: scores.iter().sum(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    items.sort_by(|a, b| b.score.total_cmp(&a.score));
+    items.truncate(max_counts);
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms() {
+        let items = terms("The Rust 1.80 release, 网络安全");
+        assert!(items.contains("rust"));
+        assert!(items.contains("release"));
+        assert!(items.contains("网络"));
+        assert!(items.contains("安全"));
+        assert!(!items.contains("the"));
+        assert!(!items.contains("1"));
+    }
+
+    #[test]
+    fn test_domain() {
+        assert_eq!(domain("https://www.example.com/feed"), "example.com");
+        assert_eq!(domain("https://blog.example.com/rss"), "blog.example.com");
+        assert_ne!(
+            domain("https://a.github.io/feed"),
+            domain("https://b.github.io/feed")
+        );
+        assert_eq!(domain("invalid"), "");
+    }
+
+    #[test]
+    fn test_recommend() {
+        let mut rust = Profile::new(
+            "Rust Blog",
+            "https://blog.rust-lang.org/feed.xml",
+            "",
+            false,
+        );
+        rust.add_text("Announcing Rust 1.80", false);
+        rust.add_text("Rust async closures", true);
+
+        let news = Profile::new("News", "https://news.example.com/rss", "News", false);
+
+        let candidates = vec![
+            Candidate {
+                name: "This Week in Rust".to_string(),
+                url: "https://this-week-in-rust.org/rss.xml".to_string(),
+                ..Default::default()
+            },
+            Candidate {
+                name: "Cooking".to_string(),
+                url: "https://cooking.org/rss".to_string(),
+                ..Default::default()
+            },
+            Candidate {
+                name: "World".to_string(),
+                url: "https://world.org/rss".to_string(),
+                category: "News/World".to_string(),
+                ..Default::default()
+            },
+            Candidate {
+                name: "Example".to_string(),
+                url: "https://www.news.example.com/feed".to_string(),
+                category: "News".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let items = recommend(&[rust, news], &candidates, 10);
+        assert_eq!(
+            items.iter().map(|item| item.index).collect::<Vec<_>>(),
+            vec![3, 2, 0]
+        );
+        assert_eq!(items[1].profile, 1);
+        assert_eq!(items[2].profile, 0);
+
+        assert_eq!(recommend(&[], &candidates, 10), vec![]);
+    }
+}
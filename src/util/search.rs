@@ -0,0 +1,152 @@
+use pinyin::ToPinyin;
+use std::cmp::Reverse;
+
+const SCORE_EXACT: u32 = 100;
+const SCORE_PREFIX: u32 = 90;
+const SCORE_CONTAINS: u32 = 80;
+const SCORE_PINYIN_PREFIX: u32 = 70;
+const SCORE_PINYIN_CONTAINS: u32 = 65;
+const SCORE_INITIALS_PREFIX: u32 = 60;
+const SCORE_INITIALS_CONTAINS: u32 = 55;
+const SCORE_URL: u32 = 50;
+const SCORE_FUZZY: u32 = 40;
+
+// The full pinyin and the initials of the text. The non-Chinese characters are kept as they are.
+pub fn pinyin(text: &str) -> (String, String) {
+    let (mut full, mut initials) = (String::default(), String::default());
+
+    for (c, py) in text.chars().zip(text.to_pinyin()) {
+        match py {
+            Some(py) => {
+                full.push_str(py.plain());
+                initials.push_str(py.first_letter());
+            }
+            _ => {
+                for c in c.to_lowercase() {
+                    full.push(c);
+                    initials.push(c);
+                }
+            }
+        }
+    }
+
+    (full, initials)
+}
+
+// The characters of the keyword appear in the text in order. The less gaps, the higher score.
+fn fuzzy(keyword: &str, text: &str) -> Option<u32> {
+    let mut chars = text.chars().enumerate();
+    let (mut gaps, mut last) = (0, None);
+
+    for k in keyword.chars() {
+        let (index, _) = chars.find(|(_, c)| *c == k)?;
+        if let Some(last) = last {
+            gaps += index - last - 1;
+        }
+        last = Some(index);
+    }
+
+    Some(SCORE_FUZZY.saturating_sub(gaps as u32).max(1))
+}
+
+// The relevance of the item to the keyword, `None` means not matched.
+// The keyword is case-insensitive, and it can be the pinyin or the initials of the Chinese name.
+pub fn score(keyword: &str, name: &str, url: &str) -> Option<u32> {
+    let keyword = keyword.trim().to_lowercase();
+    if keyword.is_empty() {
+        return Some(0);
+    }
+
+    let name = name.trim().to_lowercase();
+    if name == keyword {
+        return Some(SCORE_EXACT);
+    } else if name.starts_with(&keyword) {
+        return Some(SCORE_PREFIX);
+    } else if name.contains(&keyword) {
+        return Some(SCORE_CONTAINS);
+    }
+
+    let (full, initials) = pinyin(&name);
+    if full.starts_with(&keyword) {
+        return Some(SCORE_PINYIN_PREFIX);
+    } else if full.contains(&keyword) {
+        return Some(SCORE_PINYIN_CONTAINS);
+    } else if initials.starts_with(&keyword) {
+        return Some(SCORE_INITIALS_PREFIX);
+    } else if initials.contains(&keyword) {
+        return Some(SCORE_INITIALS_CONTAINS);
+    }
+
+    if url.to_lowercase().contains(&keyword) {
+        return Some(SCORE_URL);
+    }
+
+    fuzzy(&keyword, &name).or_else(|| fuzzy(&keyword, &full))
+}
+
+// Return the indexes of the matched items, and the most relevant one is the first
+pub fn search<'a>(keyword: &str, items: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<usize> {
+    let mut scores = items
+        .enumerate()
+        .filter_map(|(index, (name, url))| score(keyword, name, url).map(|s| (index, s)))
+        .collect::<Vec<_>>();
+
+    scores.sort_by_key(|item| Reverse(item.1));
+    scores.into_iter().map(|(index, _)| index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinyin() {
+        let (full, initials) = pinyin("阮一峰Blog");
+        assert_eq!(full, "ruanyifengblog");
+        assert_eq!(initials, "ryfblog");
+    }
+
+    #[test]
+    fn test_score() {
+        let url = "https://blog.rust-lang.org/feed.xml";
+        assert_eq!(score("rust", "Rust", url), Some(SCORE_EXACT));
+        assert_eq!(score("RUST", "Rust Blog", url), Some(SCORE_PREFIX));
+        assert_eq!(score("blog", "Rust Blog", url), Some(SCORE_CONTAINS));
+        assert_eq!(score("lang", "Rust Blog", url), Some(SCORE_URL));
+        assert_eq!(score("rsbg", "Rust Blog", url), Some(SCORE_FUZZY - 5));
+        assert_eq!(score("golang", "Rust Blog", url), None);
+        assert_eq!(score(" ", "Rust Blog", url), Some(0));
+
+        assert_eq!(
+            score("ruanyi", "阮一峰的网络日志", ""),
+            Some(SCORE_PINYIN_PREFIX)
+        );
+        assert_eq!(
+            score("wangluo", "阮一峰的网络日志", ""),
+            Some(SCORE_PINYIN_CONTAINS)
+        );
+        assert_eq!(
+            score("ryf", "阮一峰的网络日志", ""),
+            Some(SCORE_INITIALS_PREFIX)
+        );
+        assert_eq!(
+            score("wlrz", "阮一峰的网络日志", ""),
+            Some(SCORE_INITIALS_CONTAINS)
+        );
+    }
+
+    #[test]
+    fn test_search() {
+        let items = [
+            ("Go Blog", "https://go.dev/blog/feed.atom"),
+            ("Rust Blog", "https://blog.rust-lang.org/feed.xml"),
+            ("This Week in Rust", "https://this-week-in-rust.org/rss.xml"),
+            ("Rust", "https://example.com/rss"),
+        ];
+
+        assert_eq!(
+            search("rust", items.iter().map(|(n, u)| (*n, *u))),
+            vec![3, 1, 2]
+        );
+    }
+}
@@ -1,5 +1,23 @@
-use chrono::Local;
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
 
 pub fn local_now(format: &str) -> String {
     return Local::now().format(format).to_string();
 }
+
+// The format which is used by the `pubDate` of the rss feeds
+pub fn rfc2822_from_timestamp(secs: i64) -> String {
+    match Utc.timestamp_opt(secs, 0).single() {
+        Some(t) => t.to_rfc2822(),
+        _ => String::default(),
+    }
+}
+
+// The `pubDate` of the rss feeds, the `published` of the atom feeds, or the format of `DateTime::to_string`
+pub fn parse_pub_date(text: &str) -> Option<DateTime<FixedOffset>> {
+    let text = text.trim();
+
+    DateTime::parse_from_rfc2822(text)
+        .or_else(|_| DateTime::parse_from_rfc3339(text))
+        .or_else(|_| DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S %:z"))
+        .ok()
+}
@@ -61,6 +61,41 @@ pub fn tr(text: &str) -> String {
     items.insert("清除缓存失败", "Remove cache failed");
     items.insert("清除缓存成功", "Remove cache success");
     items.insert("超过1000字数限制", "Over the limit of 2048 word counts");
+    items.insert(
+        "是否删除早于所选天数的缓存？",
+        "Delete the cache older than the selected days or not?",
+    );
+    items.insert("缓存信息", "Cache information");
+    items.insert("条目", "Items");
+    items.insert("大小", "Size");
+    items.insert("缓存过期时间(天)", "Cache expiration(day)");
+    items.insert("0表示永不过期", "0 means never expire");
+    items.insert("最大缓存条目", "Max cache items");
+    items.insert("0表示不限制", "0 means unlimited");
+    items.insert("清除缓存", "Clear cache");
+    items.insert("早于", "Older than");
+    items.insert("天", "days");
+    items.insert("全部", "All");
+    items.insert("回收站保留时间(天)", "Trash retention(day)");
+    items.insert("0表示永久保留", "0 means keep forever");
+    items.insert("回收站", "Trash");
+    items.insert("文章", "Entries");
+    items.insert("数据库", "Database");
+    items.insert("可回收", "Reclaimable");
+    items.insert("上次整理", "Last maintenance");
+    items.insert("从未", "Never");
+    items.insert("自动整理间隔(天)", "Auto maintenance interval(day)");
+    items.insert("0表示不自动整理", "0 means never");
+    items.insert("完整性检查", "Integrity check");
+    items.insert("整理数据库", "Maintain database");
+    items.insert("检查失败", "Check failed");
+    items.insert("数据库完整", "Database is intact");
+    items.insert("数据库损坏", "Database is corrupted");
+    items.insert("整理数据库失败", "Maintain database failed");
+    items.insert("整理数据库成功", "Maintain database success");
+    items.insert("已删除", "Deleted");
+    items.insert("撤销", "Undo");
+    items.insert("订阅不存在", "Subscription not exists");
 
     items.insert("界 面", "UI");
     items.insert("阅 读", "Reading");
@@ -136,8 +171,65 @@ pub fn tr(text: &str) -> String {
     items.insert("备份失败", "Backup failed");
     items.insert("恢复成功", "Recover success");
     items.insert("恢复失败", "Recover failed");
+    items.insert("已读状态", "Read state");
+    items.insert("黑名单", "Blacklist");
+    items.insert("本地备份", "Local backup");
+    items.insert(
+        "备份目录，为空则使用默认目录",
+        "Backup directory. Empty means the default directory",
+    );
+    items.insert("导出到本地", "Export");
+    items.insert("刷新", "Refresh");
+    items.insert("不支持的备份版本", "Unsupported backup version");
     items.insert("是否备份？", "Backup or not?");
     items.insert("是否恢复？", "Recover or not?");
+    items.insert("恢复计划", "Recover plan");
+    items.insert("加密密码", "Passphrase");
+    items.insert("WebDAV目录地址", "WebDAV directory url");
+    items.insert("用户名", "Username");
+    items.insert("应用密码", "App password");
+    items.insert("上传", "Upload");
+    items.insert(
+        "为空则不加密备份",
+        "Empty means the backups are not encrypted",
+    );
+    items.insert("已加密", "Encrypted");
+    items.insert(
+        "备份已加密，请输入密码",
+        "The backup is encrypted, please input the passphrase",
+    );
+    items.insert(
+        "密码错误或备份已损坏",
+        "Wrong passphrase or the backup is corrupted",
+    );
+    items.insert("新增订阅", "Added RSS");
+    items.insert("更新订阅", "Updated RSS");
+    items.insert("删除订阅", "Removed RSS");
+    items.insert(
+        "恢复时删除备份中没有的订阅",
+        "Remove the RSS which are not in the backup when recovering",
+    );
+    items.insert("新增收藏", "Added favorites");
+
+    items.insert("服务器地址", "Server URL");
+    items.insert("为空则使用默认服务器", "Empty means the default server");
+
+    items.insert("OPML导入导出", "OPML import and export");
+    items.insert("文件路径", "File path");
+    items.insert(
+        "导出时为空则使用备份目录",
+        "Empty means the backup directory when exporting",
+    );
+    items.insert("导出", "Export");
+    items.insert("导入", "Import");
+    items.insert("预览", "Preview");
+    items.insert("已存在", "Existed");
+    items.insert("导出成功", "Export success");
+    items.insert("导出失败", "Export failed");
+    items.insert("导入成功", "Import success");
+    items.insert("导入失败", "Import failed");
+    items.insert("解析失败", "Parse failed");
+    items.insert("读取失败", "Read failed");
 
     items.insert("获取最新版本", "Latest version");
     items.insert("版本信息", "Current version");
@@ -164,6 +256,38 @@ pub fn tr(text: &str) -> String {
         "Welcome! Enjoying you journey of reading.",
     );
     items.insert("请选择语言", "Please select language");
+    items.insert("账 户", "Account");
+    items.insert("同步方式", "Synchronization");
+    items.insert("密码", "Password");
+    items.insert("登录", "Login");
+    items.insert("同步", "Sync");
+    items.insert("未设置账户", "No account is set");
+    items.insert("正在登录...", "Logging in...");
+    items.insert("登录失败", "Login failed");
+    items.insert("登录成功", "Login success");
+    items.insert("同步账户失败", "Sync account failed");
+    items.insert(
+        "用户名，为空则使用API令牌",
+        "Username, empty means using the api token",
+    );
+    items.insert("API令牌", "API token");
+    items.insert("自动备份", "Auto backup");
+    items.insert("自动备份失败", "Auto backup failed");
+    items.insert("上次自动备份", "Last auto backup");
+    items.insert("保留的自动备份数量", "Counts of the auto backups to keep");
+    items.insert("正在加载...", "Loading...");
+    items.insert("预览失败", "Preview failed");
+    items.insert("最新文章", "Latest entries");
+    items.insert("未知", "Unknown");
+    items.insert("篇/天", "posts/day");
+    items.insert("篇/周", "posts/week");
+    items.insert("篇/月", "posts/month");
+    items.insert("正在订阅...", "Subscribing...");
+    items.insert("同步失败", "Sync failed");
+    items.insert("入门合集", "Starter bundles");
+    items.insert("个订阅源", "feeds");
+    items.insert("为你推荐", "Recommended for you");
+    items.insert("因为你订阅了", "Because you follow");
 
     if let Some(txt) = items.get(text) {
         return txt.to_string();
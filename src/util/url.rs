@@ -0,0 +1,63 @@
+use reqwest::Url;
+
+// Normalize a feed url, so that the same feed can be matched even if the urls
+// are written differently, such as `http` and `https`, `www.` prefix,
+// upper case host, default port, trailing slash and fragment.
+pub fn normalize(url: &str) -> String {
+    let url = url.trim();
+
+    let Ok(u) = Url::parse(url) else {
+        return url.trim_end_matches('/').to_lowercase();
+    };
+
+    let host = u
+        .host_str()
+        .unwrap_or_default()
+        .trim_start_matches("www.")
+        .to_string();
+
+    // the default port has been removed by the parser
+    let port = match u.port() {
+        Some(port) => format!(":{port}"),
+        _ => String::default(),
+    };
+
+    let path = u.path().trim_end_matches('/');
+
+    let query = match u.query() {
+        Some(query) if !query.is_empty() => format!("?{query}"),
+        _ => String::default(),
+    };
+
+    format!("{host}{port}{path}{query}")
+}
+
+pub fn is_same(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let verify = "example.com/feed?a=1";
+        for item in [
+            "https://example.com/feed?a=1",
+            "http://example.com/feed?a=1",
+            "https://www.Example.com/feed/?a=1",
+            "https://example.com:443/feed?a=1#top",
+            " https://example.com/feed?a=1 ",
+        ] {
+            assert_eq!(normalize(item), verify, "{item}");
+        }
+
+        assert_eq!(normalize("https://example.com:8080/"), "example.com:8080");
+        assert_ne!(
+            normalize("https://example.com/Feed"),
+            normalize("https://example.com/feed")
+        );
+        assert!(is_same("example.com/feed/", "EXAMPLE.com/feed"));
+    }
+}
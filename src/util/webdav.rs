@@ -0,0 +1,357 @@
+use super::http::{self, ProxyType};
+use anyhow::{bail, Result};
+use quick_xml::{events::Event, Reader};
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE, RANGE},
+    Method, RequestBuilder, StatusCode,
+};
+use std::time::Duration;
+
+const TIMEOUT: u64 = 30;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:resourcetype/>
+    <d:getcontentlength/>
+    <d:getlastmodified/>
+  </d:prop>
+</d:propfind>"#;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DavFile {
+    pub name: String,
+    pub size: u64,
+}
+
+// A minimal WebDAV client which treats the url as a directory of files
+pub struct WebDav {
+    url: String,
+    username: String,
+    password: String,
+    proxy_type: Option<ProxyType>,
+}
+
+impl WebDav {
+    pub fn new(url: &str, username: &str, password: &str, proxy_type: Option<ProxyType>) -> Self {
+        Self {
+            url: url.trim().trim_end_matches('/').to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            proxy_type,
+        }
+    }
+
+    fn file_url(&self, name: &str) -> String {
+        format!("{}/{}", self.url, name)
+    }
+
+    fn request(&self, method: Method, url: &str) -> Result<RequestBuilder> {
+        let proxy_type = match self.proxy_type {
+            Some(ProxyType::Http) => Some(ProxyType::Http),
+            Some(ProxyType::Socks5) => Some(ProxyType::Socks5),
+            _ => None,
+        };
+
+        let mut req = http::client(proxy_type)?
+            .request(method, url)
+            .timeout(Duration::from_secs(TIMEOUT));
+
+        if !self.username.is_empty() {
+            req = req.basic_auth(&self.username, Some(&self.password));
+        }
+
+        Ok(req)
+    }
+
+    // Create the directory. The server responds `405` if it has existed.
+    pub async fn mkdir(&self) -> Result<()> {
+        let res = self
+            .request(Method::from_bytes(b"MKCOL")?, &format!("{}/", self.url))?
+            .send()
+            .await?;
+
+        match res.status() {
+            s if s.is_success() || s == StatusCode::METHOD_NOT_ALLOWED => Ok(()),
+            s => bail!("http error code: {}", s.as_str()),
+        }
+    }
+
+    pub async fn put(&self, name: &str, text: String) -> Result<()> {
+        self.mkdir().await?;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+
+        let res = self
+            .request(Method::PUT, &self.file_url(name))?
+            .headers(headers)
+            .body(text)
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        Ok(())
+    }
+
+    pub async fn get(&self, name: &str) -> Result<String> {
+        let res = self
+            .request(Method::GET, &self.file_url(name))?
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        Ok(res.text().await?)
+    }
+
+    // Only download the first `len` bytes, the whole file is read until `len` if the server ignores the range
+    pub async fn get_head(&self, name: &str, len: usize) -> Result<String> {
+        let mut res = self
+            .request(Method::GET, &self.file_url(name))?
+            .header(RANGE, format!("bytes=0-{}", len.saturating_sub(1)))
+            .send()
+            .await?;
+
+        if !res.status().is_success() {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        let mut head = vec![];
+        while head.len() < len {
+            match res.chunk().await? {
+                Some(chunk) => head.extend_from_slice(&chunk),
+                _ => break,
+            }
+        }
+        head.truncate(len);
+
+        Ok(String::from_utf8_lossy(&head).to_string())
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let res = self
+            .request(Method::DELETE, &self.file_url(name))?
+            .send()
+            .await?;
+
+        if !res.status().is_success() && res.status() != StatusCode::NOT_FOUND {
+            bail!("http error code: {}", res.status().as_str());
+        }
+
+        Ok(())
+    }
+
+    // The files in the directory, the sub-directories are ignored
+    pub async fn list(&self) -> Result<Vec<DavFile>> {
+        let mut headers = HeaderMap::new();
+        headers.insert("Depth", "1".parse().unwrap());
+        headers.insert(CONTENT_TYPE, "application/xml".parse().unwrap());
+
+        let res = self
+            .request(Method::from_bytes(b"PROPFIND")?, &format!("{}/", self.url))?
+            .headers(headers)
+            .body(PROPFIND_BODY)
+            .send()
+            .await?;
+
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(vec![]),
+            s if s.is_success() => parse_propfind(&res.text().await?),
+            s => bail!("http error code: {}", s.as_str()),
+        }
+    }
+}
+
+// The elements have different namespace prefixes in different servers, so only the local names are checked
+pub fn parse_propfind(text: &str) -> Result<Vec<DavFile>> {
+    let mut reader = Reader::from_str(text);
+    reader.trim_text(true);
+
+    let mut items = vec![];
+    let (mut href, mut size, mut is_collection) = (String::default(), 0, false);
+    let mut current = vec![];
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                if name == b"response" {
+                    (href, size, is_collection) = (String::default(), 0, false);
+                } else if name == b"collection" {
+                    is_collection = true;
+                }
+                current.push(name);
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"collection" => is_collection = true,
+            Event::Text(e) => match current.last().map(|v| v.as_slice()) {
+                Some(b"href") => href = e.unescape()?.trim().to_string(),
+                Some(b"getcontentlength") => size = e.unescape()?.trim().parse().unwrap_or(0),
+                _ => (),
+            },
+            Event::End(e) => {
+                current.pop();
+
+                if e.local_name().as_ref() == b"response" && !is_collection {
+                    let name = href.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+                    if !name.is_empty() {
+                        items.push(DavFile {
+                            name: name.to_string(),
+                            size,
+                        });
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    const PROPFIND_RESPONSE: &str = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/remote.php/dav/files/user/rssbox/</d:href>
+    <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/user/rssbox/rssbox-backup-20240101-120000.json</d:href>
+    <d:propstat><d:prop><d:resourcetype/><d:getcontentlength>1024</d:getcontentlength></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+    #[test]
+    fn test_parse_propfind() -> Result<()> {
+        let items = parse_propfind(PROPFIND_RESPONSE)?;
+        assert_eq!(
+            items,
+            vec![DavFile {
+                name: "rssbox-backup-20240101-120000.json".to_string(),
+                size: 1024,
+            }]
+        );
+        Ok(())
+    }
+
+    // A local WebDAV stand-in which keeps the files in memory. One request per connection.
+    async fn serve_webdav() -> Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let files: Arc<Mutex<HashMap<String, String>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let files = files.clone();
+                tokio::spawn(async move {
+                    let mut reader = BufReader::new(stream);
+                    let mut line = String::default();
+                    _ = reader.read_line(&mut line).await;
+                    let parts = line.split_whitespace().collect::<Vec<_>>();
+                    let (method, path) = (parts[0].to_string(), parts[1].to_string());
+
+                    let mut content_length = 0;
+                    loop {
+                        let mut header = String::default();
+                        _ = reader.read_line(&mut header).await;
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                        if let Some(v) = header.to_lowercase().strip_prefix("content-length:") {
+                            content_length = v.trim().parse().unwrap_or(0);
+                        }
+                    }
+
+                    let mut body = vec![0; content_length];
+                    _ = reader.read_exact(&mut body).await;
+
+                    let (status, text) = {
+                        let mut files = files.lock().unwrap();
+                        match method.as_str() {
+                            "MKCOL" => ("405 Method Not Allowed", String::default()),
+                            "PUT" => {
+                                files.insert(path, String::from_utf8_lossy(&body).to_string());
+                                ("201 Created", String::default())
+                            }
+                            "GET" => match files.get(&path) {
+                                Some(text) => ("200 OK", text.clone()),
+                                _ => ("404 Not Found", String::default()),
+                            },
+                            "DELETE" => match files.remove(&path) {
+                                Some(_) => ("204 No Content", String::default()),
+                                _ => ("404 Not Found", String::default()),
+                            },
+                            "PROPFIND" => {
+                                let responses = files
+                                    .iter()
+                                    .map(|(k, v)| {
+                                        format!(
+                                            "<D:response><D:href>{k}</D:href><D:propstat><D:prop><D:resourcetype/><D:getcontentlength>{}</D:getcontentlength></D:prop></D:propstat></D:response>",
+                                            v.len()
+                                        )
+                                    })
+                                    .collect::<String>();
+                                (
+                                    "207 Multi-Status",
+                                    format!(
+                                        r#"<D:multistatus xmlns:D="DAV:"><D:response><D:href>{path}</D:href><D:propstat><D:prop><D:resourcetype><D:collection/></D:resourcetype></D:prop></D:propstat></D:response>{responses}</D:multistatus>"#
+                                    ),
+                                )
+                            }
+                            _ => ("405 Method Not Allowed", String::default()),
+                        }
+                    };
+
+                    let mut stream = reader.into_inner();
+                    let res = format!(
+                        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{text}",
+                        text.len()
+                    );
+                    _ = stream.write_all(res.as_bytes()).await;
+                    _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        Ok(format!("http://{addr}/dav/rssbox"))
+    }
+
+    #[tokio::test]
+    async fn test_webdav() -> Result<()> {
+        let url = serve_webdav().await?;
+        let dav = WebDav::new(&url, "user", "password", None);
+
+        dav.put("backup-1.json", "text-1".to_string()).await?;
+        dav.put("backup-2.json", "text-22".to_string()).await?;
+
+        let mut items = dav.list().await?;
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].size, 7);
+
+        assert_eq!(dav.get("backup-1.json").await?, "text-1");
+
+        dav.delete("backup-1.json").await?;
+        assert!(dav.get("backup-1.json").await.is_err());
+        assert_eq!(dav.list().await?.len(), 1);
+        Ok(())
+    }
+}